@@ -0,0 +1,236 @@
+use std::env;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+use util::bench::{self, Results};
+use util::{animate, image, Answers, Day, Error, Input};
+
+const USAGE: &str = "usage: aoc [all | DAY | FIRST-LAST] [--input INPUT] [--verify] [--save]
+       aoc [all | DAY | FIRST-LAST] [--input INPUT] --bench [--runs N] [--warmup N]
+           [--baseline FILE] [--output FILE] [--threshold PERCENT]";
+
+/// Every day there's a solution for, in order.
+const DAYS: &[&dyn Day] = &[
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+    &day09::Day09,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+];
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+fn parse_days(arg: &str) -> Option<RangeInclusive<u32>> {
+    if arg == "all" {
+        return Some(1..=25);
+    }
+    let mut bounds = arg.splitn(2, '-');
+    let first = bounds.next()?.parse().ok()?;
+    let last = bounds
+        .next()
+        .map_or(Some(first), |last| last.parse().ok())?;
+    Some(first..=last)
+}
+
+/// The directory of a day's crate, where its inputs are kept.
+fn day_dir(day: u32) -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "..", &format!("day{:02}", day)]
+        .iter()
+        .collect()
+}
+
+/// Solves each day, printing the answers or, when verifying, whether they're right.
+fn run(days: &[&dyn Day], input: &Input, verify: bool, save: bool) -> bool {
+    let (animation, pictures) = (animate::Options::from_env(), image::Options::from_env());
+    let mut total = Duration::default();
+    let mut failed = false;
+    for day in days {
+        let dir = day_dir(day.day());
+        let result = input.read_in(&dir).and_then(|source| {
+            let report = util::run(*day, input, &source)?;
+            let answers = input.known_answers_in(&dir, &source)?;
+            Ok((source, answers, report))
+        });
+        let (source, answers, report) = match result {
+            Ok(result) => result,
+            Err(err) => {
+                failed = true;
+                eprintln!("day {}: {}\n", day.day(), err);
+                continue;
+            }
+        };
+        total += report.time();
+        if !verify {
+            if let Err(err) = util::visualise(*day, input, &source, &animation, &pictures) {
+                failed = true;
+                eprintln!("day {}: {}\n", day.day(), err);
+            }
+        }
+
+        let mismatches = answers
+            .as_ref()
+            .map(|answers| answers.check(&report))
+            .unwrap_or_default();
+        failed |= !mismatches.is_empty();
+        if verify {
+            if answers.is_none() {
+                println!("day {}: no answers", day.day());
+            } else if mismatches.is_empty() {
+                println!("day {}: ok ({:.1?})", day.day(), report.time());
+            }
+            for mismatch in &mismatches {
+                println!("day {}: {}", day.day(), mismatch);
+            }
+        } else {
+            println!("{}", report);
+            for mismatch in &mismatches {
+                eprintln!("day {}: {}\n", day.day(), mismatch);
+            }
+        }
+
+        if save {
+            if let Err(err) = input.save_answers_in(&dir, &Answers::from_report(&report)) {
+                failed = true;
+                eprintln!("day {}: {}", day.day(), err);
+            }
+        }
+    }
+    println!("total: {:.1?}", total);
+    failed
+}
+
+/// Times each day, saving the timings to `output` and comparing them with `baseline`.
+fn bench(
+    days: &[&dyn Day],
+    input: &Input,
+    options: &bench::Options,
+    baseline: Option<&Path>,
+    output: &Path,
+    threshold: f64,
+) -> bool {
+    let baseline = match baseline {
+        Some(path) => Some(path),
+        // compare with the last run, if there was one
+        None => Some(output).filter(|output| output.exists()),
+    };
+    let baseline = match baseline.map(Results::load).transpose() {
+        Ok(baseline) => baseline,
+        Err(err) => {
+            eprintln!("{}", err);
+            return true;
+        }
+    };
+
+    let mut failed = false;
+    let mut results = Results::default();
+    println!(
+        "{:<6}  {:<6}  {:>10}  {:>10}  {:>10}",
+        "", "", "min", "median", "max"
+    );
+    for day in days {
+        let dir = day_dir(day.day());
+        let result = input.read_in(&dir).and_then(|source| {
+            day.bench(&source, options).map_err(|error| Error::Invalid {
+                input: input.to_string(),
+                error,
+            })
+        });
+        match result {
+            Ok(bench) => {
+                print!("{}", bench);
+                results.days.push(bench);
+            }
+            Err(err) => {
+                failed = true;
+                eprintln!("day {}: {}", day.day(), err);
+            }
+        }
+    }
+
+    if let Some(baseline) = baseline {
+        let changes = results.compare(&baseline, threshold);
+        if !changes.is_empty() {
+            println!();
+        }
+        for change in changes {
+            failed |= change.is_regression();
+            println!("{}", change);
+        }
+    }
+    // keep the timings of the days that weren't run this time
+    if let Ok(previous) = Results::load(output) {
+        results.merge(&previous);
+    }
+    if let Err(err) = results.save(output) {
+        failed = true;
+        eprintln!("{}", err);
+    }
+    failed
+}
+
+fn main() {
+    let mut days = 1..=25;
+    let mut input = Input::Default;
+    let mut verify = false;
+    let mut save = false;
+    let mut benching = false;
+    let mut options = bench::Options::default();
+    let mut baseline = None;
+    let mut output = [env!("CARGO_MANIFEST_DIR"), "..", "target", "bench.json"]
+        .iter()
+        .collect::<PathBuf>();
+    let mut threshold = 10.0;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--input" => input = Input::parse(&value()),
+            "--verify" => verify = true,
+            "--save" => save = true,
+            "--bench" => benching = true,
+            "--runs" => options.runs = value().parse().unwrap_or_else(|_| usage()),
+            "--warmup" => options.warmup = value().parse().unwrap_or_else(|_| usage()),
+            "--baseline" => baseline = Some(PathBuf::from(value())),
+            "--output" => output = PathBuf::from(value()),
+            "--threshold" => threshold = value().parse().unwrap_or_else(|_| usage()),
+            _ => days = parse_days(&arg).unwrap_or_else(|| usage()),
+        }
+    }
+
+    let days = DAYS
+        .iter()
+        .copied()
+        .filter(|day| days.contains(&day.day()))
+        .collect::<Vec<_>>();
+    let failed = if benching {
+        bench(
+            &days,
+            &input,
+            &options,
+            baseline.as_deref(),
+            &output,
+            threshold / 100.0,
+        )
+    } else {
+        run(&days, &input, verify, save)
+    };
+    if failed {
+        process::exit(1);
+    }
+}
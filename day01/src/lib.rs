@@ -0,0 +1,54 @@
+use std::error::Error;
+use util::{parse, Solution};
+
+fn fuel_required(input: i64) -> i64 {
+    (input / 3) - 2
+}
+
+fn fuel_required_incl_fuel(input: i64) -> i64 {
+    let mut prev = fuel_required(input);
+    let mut req = prev;
+    loop {
+        let new = fuel_required(prev);
+        if new <= 0 {
+            break req;
+        }
+        prev = new;
+        req += new;
+    }
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u32 = 1;
+
+    type Input = Vec<i64>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(source: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+        Ok(parse::lines(source, |line| line.int())?)
+    }
+
+    fn part1(modules: &Vec<i64>) -> i64 {
+        modules.iter().cloned().map(fuel_required).sum()
+    }
+
+    fn part2(modules: &Vec<i64>) -> i64 {
+        modules.iter().cloned().map(fuel_required_incl_fuel).sum()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test() {
+    assert_eq!(fuel_required(12), 2);
+    assert_eq!(fuel_required(14), 2);
+    assert_eq!(fuel_required(1969), 654);
+    assert_eq!(fuel_required(100756), 33583);
+
+    assert_eq!(fuel_required_incl_fuel(14), 2);
+    assert_eq!(fuel_required_incl_fuel(1969), 966);
+    assert_eq!(fuel_required_incl_fuel(100756), 50346);
+}
@@ -0,0 +1,41 @@
+use intcode::parallel::Batch;
+use intcode::program::Program;
+use intcode::Machine;
+use std::error::Error;
+use util::Solution;
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u32 = 2;
+
+    type Input = Machine;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(source: &str) -> Result<Machine, Box<dyn Error>> {
+        Ok(Machine::new(Program::parse(source)?.image()))
+    }
+
+    fn part1(machine: &Machine) -> i64 {
+        let mut machine = machine.clone();
+        machine.patch(&[(1, 12), (2, 2)]);
+        machine.run();
+        machine.memory()[0]
+    }
+
+    fn part2(machine: &Machine) -> i64 {
+        let inputs = (0..100)
+            .flat_map(|noun| (0..100).map(move |verb| (noun, verb)))
+            .collect::<Vec<_>>();
+        let batch = Batch::new(machine.clone());
+        let (_, answer) = batch
+            .find(&inputs, |machine, &(noun, verb)| {
+                machine.patch(&[(1, noun), (2, verb)]);
+                machine.run();
+                Some(100 * noun + verb).filter(|_| machine.memory()[0] == 19_690_720)
+            })
+            .unwrap();
+        answer
+    }
+}
@@ -0,0 +1,119 @@
+use std::cmp::{max, min};
+use std::error::Error;
+use util::geometry::{Direction, Point};
+use util::parse::{self, Cursor, ParseError};
+use util::Solution;
+
+/// A straight run of wire, and how far along the wire it starts.
+#[derive(Clone, Copy)]
+struct Segment {
+    start: Point,
+    end: Point,
+    steps: i64,
+}
+
+/// Reads a wire's path, like `R8,U5,L5,D3`.
+fn parse_moves(line: &mut Cursor<'_>) -> Result<Vec<(Direction, i64)>, ParseError> {
+    line.sep_by(",", |fragment| {
+        let dir = fragment.char("U, D, L or R", |c| match c {
+            'U' | 'D' | 'L' | 'R' => Direction::from_char(c),
+            _ => None,
+        })?;
+        Ok((dir, fragment.int()?))
+    })
+}
+
+fn wire(moves: &[(Direction, i64)]) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut pos = Point::ORIGIN;
+    let mut steps = 0;
+    for (dir, num) in moves {
+        let end = pos + dir.vector() * *num;
+        segments.push(Segment {
+            start: pos,
+            end,
+            steps,
+        });
+        pos = end;
+        steps += num;
+    }
+    segments
+}
+
+fn between(x: i64, a: i64, b: i64) -> bool {
+    min(a, b) <= x && x <= max(a, b)
+}
+
+fn intersection(a: Segment, b: Segment) -> Option<Point> {
+    if a.start.x == a.end.x
+        && b.start.y == b.end.y
+        && between(a.start.x, b.start.x, b.end.x)
+        && between(b.start.y, a.start.y, a.end.y)
+    {
+        Some(Point::new(a.start.x, b.start.y))
+    } else if a.start.y == a.end.y
+        && b.start.x == b.end.x
+        && between(a.start.y, b.start.y, b.end.y)
+        && between(b.start.x, a.start.x, a.end.x)
+    {
+        Some(Point::new(b.start.x, a.start.y))
+    } else {
+        None
+    }
+}
+
+// Each intersection comes with the number of steps along both wires to get there.
+fn intersections(a: &[(Direction, i64)], b: &[(Direction, i64)]) -> Vec<(Point, i64)> {
+    let mut v = Vec::new();
+    let b = wire(b);
+    for a in wire(a) {
+        for b in &b {
+            if let Some(ix) = intersection(a, *b) {
+                if ix != Point::ORIGIN {
+                    let a_steps = a.steps + a.start.manhattan(ix);
+                    let b_steps = b.steps + b.start.manhattan(ix);
+                    v.push((ix, a_steps + b_steps));
+                }
+            }
+        }
+    }
+    v
+}
+
+#[test]
+fn test_intersections() {
+    let wires = parse::lines("R8,U5,L5,D3\nU7,R6,D4,L4", parse_moves).unwrap();
+    assert_eq!(
+        intersections(&wires[0], &wires[1]),
+        vec![(Point::new(6, -5), 30), (Point::new(3, -3), 40)]
+    );
+}
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u32 = 3;
+
+    type Input = Vec<(Point, i64)>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(source: &str) -> Result<Vec<(Point, i64)>, Box<dyn Error>> {
+        match parse::lines(source, parse_moves)?.as_slice() {
+            [a, b] => Ok(intersections(a, b)),
+            _ => Err("expected two wires".into()),
+        }
+    }
+
+    fn part1(intersections: &Vec<(Point, i64)>) -> i64 {
+        intersections
+            .iter()
+            .map(|(ix, _)| Point::ORIGIN.manhattan(*ix))
+            .min()
+            .unwrap()
+    }
+
+    fn part2(intersections: &Vec<(Point, i64)>) -> i64 {
+        intersections.iter().map(|(_, s)| *s).min().unwrap()
+    }
+}
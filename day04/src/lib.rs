@@ -0,0 +1,67 @@
+#![allow(clippy::trivially_copy_pass_by_ref)]
+
+use std::error::Error;
+use std::ops::RangeInclusive;
+use util::{parse, Solution};
+
+fn valid_password_part1(s: &u32) -> bool {
+    let s = s.to_string().bytes().collect::<Vec<_>>();
+    s.len() == 6 && s.windows(2).any(|w| w[0] == w[1]) && s.windows(2).all(|w| w[0] <= w[1])
+}
+
+fn valid_password_part2(s: &u32) -> bool {
+    let mut last = b'A';
+    let mut count = 0;
+    for b in s.to_string().bytes() {
+        if b == last {
+            count += 1;
+        } else {
+            if count == 2 {
+                return true;
+            }
+            last = b;
+            count = 1;
+        }
+    }
+    count == 2
+}
+
+#[test]
+fn test_valid_password() {
+    assert!(valid_password_part1(&111111));
+    assert!(!valid_password_part1(&223450));
+    assert!(!valid_password_part1(&123789));
+
+    assert!(valid_password_part2(&112233));
+    assert!(!valid_password_part2(&123444));
+    assert!(valid_password_part2(&111122));
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u32 = 4;
+
+    type Input = RangeInclusive<u32>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(source: &str) -> Result<RangeInclusive<u32>, Box<dyn Error>> {
+        Ok(parse::whole(source, |range| {
+            let start = range.int()?;
+            range.literal("-")?;
+            Ok(start..=range.int()?)
+        })?)
+    }
+
+    fn part1(range: &RangeInclusive<u32>) -> usize {
+        range.clone().filter(valid_password_part1).count()
+    }
+
+    fn part2(range: &RangeInclusive<u32>) -> usize {
+        range
+            .clone()
+            .filter(|s| valid_password_part1(s) && valid_password_part2(s))
+            .count()
+    }
+}
@@ -0,0 +1,35 @@
+use intcode::program::Program;
+use intcode::{PollExt, Runner};
+use std::error::Error;
+use util::Solution;
+
+/// Runs the diagnostic program for the system with the given ID, returning the diagnostic code it
+/// outputs last.
+fn diagnostic(program: &[i64], system: i64) -> i64 {
+    let mut program = program.to_vec();
+    let mut runner = Runner::new(&mut program);
+    runner.input(system);
+    runner.map(|output| output.unwrap()).last().unwrap()
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u32 = 5;
+
+    type Input = Vec<i64>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(source: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+        Ok(Program::parse(source)?.image())
+    }
+
+    fn part1(program: &Vec<i64>) -> i64 {
+        diagnostic(program, 1)
+    }
+
+    fn part2(program: &Vec<i64>) -> i64 {
+        diagnostic(program, 5)
+    }
+}
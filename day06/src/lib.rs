@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::error::Error;
+use util::parse::{self, ParseError};
+use util::{search, Solution};
+
+/// Maps each object to the object it orbits, from lines like `COM)B`.
+fn build_tree(s: &str) -> Result<HashMap<String, String>, ParseError> {
+    let orbits = parse::lines(s, |line| {
+        let a = line.ident()?;
+        line.literal(")")?;
+        Ok((line.ident()?.to_owned(), a.to_owned()))
+    })?;
+    Ok(orbits.into_iter().collect())
+}
+
+/// The objects directly orbiting each object.
+fn children(map: &HashMap<String, String>) -> HashMap<&str, Vec<&str>> {
+    let mut children = HashMap::new();
+    for (child, parent) in map {
+        children
+            .entry(parent.as_str())
+            .or_insert_with(Vec::new)
+            .push(child.as_str());
+    }
+    children
+}
+
+fn orbit_count(map: &HashMap<String, String>) -> usize {
+    let children = children(map);
+    // each object orbits everything between it and the centre of mass
+    let layers = search::flood("COM", |key| children.get(key).cloned().unwrap_or_default());
+    layers
+        .iter()
+        .enumerate()
+        .map(|(depth, layer)| depth * layer.len())
+        .sum()
+}
+
+fn transfer_count(a: &str, b: &str, map: &HashMap<String, String>) -> usize {
+    let children = children(map);
+    let neighbours = |key: &&str| {
+        let mut v = children.get(key).cloned().unwrap_or_default();
+        v.extend(map.get(*key).map(String::as_str));
+        v
+    };
+    let path = search::bfs(map[a].as_str(), neighbours, |key| *key == map[b]).unwrap();
+    path.len() - 1
+}
+
+#[test]
+fn test_part1() {
+    let data = "COM)B
+        B)C
+        C)D
+        D)E
+        E)F
+        B)G
+        G)H
+        D)I
+        E)J
+        J)K
+        K)L";
+    let tree = build_tree(data).unwrap();
+    assert_eq!(tree.len(), 11);
+    assert_eq!(tree.get("C"), Some(&"B".to_string()));
+    assert!(!tree.contains_key("COM"));
+    assert_eq!(orbit_count(&tree), 42);
+}
+
+#[test]
+fn test_part2() {
+    let data = "COM)B
+        B)C
+        C)D
+        D)E
+        E)F
+        B)G
+        G)H
+        D)I
+        E)J
+        J)K
+        K)L
+        K)YOU
+        I)SAN";
+    let tree = build_tree(data).unwrap();
+    assert_eq!(transfer_count("YOU", "SAN", &tree), 4);
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u32 = 6;
+
+    type Input = HashMap<String, String>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(source: &str) -> Result<HashMap<String, String>, Box<dyn Error>> {
+        Ok(build_tree(source)?)
+    }
+
+    fn part1(tree: &HashMap<String, String>) -> usize {
+        orbit_count(tree)
+    }
+
+    fn part2(tree: &HashMap<String, String>) -> usize {
+        transfer_count("YOU", "SAN", tree)
+    }
+}
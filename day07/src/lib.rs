@@ -0,0 +1,108 @@
+use intcode::parallel::Batch;
+use intcode::program::Program;
+use intcode::{Machine, PollExt};
+use itertools::Itertools;
+use std::error::Error;
+use util::Solution;
+
+fn find_max_part1(program: &[i64]) -> i64 {
+    let permutations = (0..5).permutations(5).collect::<Vec<_>>();
+    let batch = Batch::new(Machine::new(program));
+    let results = batch.map(&permutations, |machine, phase_settings| {
+        let mut input = 0;
+        for phase in phase_settings {
+            machine.reset();
+            machine.extend_input(vec![*phase, input]);
+            input = machine.next().unwrap().unwrap();
+        }
+        input
+    });
+    results.into_iter().max().unwrap()
+}
+
+#[test]
+fn test_find_max_part1() {
+    assert_eq!(
+        find_max_part1(&[3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0]),
+        43210
+    );
+    assert_eq!(
+        find_max_part1(&[
+            3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23, 23, 4, 23,
+            99, 0, 0
+        ]),
+        54321
+    );
+    assert_eq!(
+        find_max_part1(&[
+            3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7, 33, 1,
+            33, 31, 31, 1, 32, 31, 31, 4, 31, 99, 0, 0, 0
+        ]),
+        65210
+    );
+}
+
+fn find_max_part2(program: &[i64]) -> i64 {
+    let permutations = (5..10).permutations(5).collect::<Vec<_>>();
+    let batch = Batch::new(Machine::new(program));
+    let results = batch.map(&permutations, |machine, phase_settings| {
+        let mut machines = vec![machine.clone(); 5];
+        for (machine, phase) in machines.iter_mut().zip(phase_settings) {
+            machine.input(*phase);
+        }
+        let mut input = 0;
+        'outer: loop {
+            for machine in &mut machines {
+                machine.input(input);
+                if let Some(new_input) = machine.next() {
+                    input = new_input.unwrap();
+                } else {
+                    break 'outer;
+                }
+            }
+        }
+        input
+    });
+    results.into_iter().max().unwrap()
+}
+
+#[test]
+fn test_find_max_part2() {
+    assert_eq!(
+        find_max_part2(&[
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5
+        ]),
+        139629729
+    );
+    assert_eq!(
+        find_max_part2(&[
+            3, 52, 1001, 52, -5, 52, 3, 53, 1, 52, 56, 54, 1007, 54, 5, 55, 1005, 55, 26, 1001, 54,
+            -5, 54, 1105, 1, 12, 1, 53, 54, 53, 1008, 54, 0, 55, 1001, 55, 1, 55, 2, 53, 55, 53, 4,
+            53, 1001, 56, -1, 56, 1005, 56, 6, 99, 0, 0, 0, 0, 10
+        ]),
+        18216
+    );
+}
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u32 = 7;
+
+    type Input = Vec<i64>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(source: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+        Ok(Program::parse(source)?.image())
+    }
+
+    fn part1(program: &Vec<i64>) -> i64 {
+        find_max_part1(program)
+    }
+
+    fn part2(program: &Vec<i64>) -> i64 {
+        find_max_part2(program)
+    }
+}
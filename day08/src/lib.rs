@@ -0,0 +1,70 @@
+use std::error::Error;
+use util::grid::Grid;
+use util::image::{Image, Palette};
+use util::Solution;
+
+fn part1(data: &[u8]) -> usize {
+    let mut counts = Vec::new();
+    for i in 0..(data.len() / 150) {
+        let subdata = &data[(i * 150)..((i + 1) * 150)];
+        let mut count = [0, 0, 0];
+        for byte in subdata {
+            count[(*byte as usize) - 48] += 1;
+        }
+        counts.push(count);
+    }
+    let min = counts.iter().min_by_key(|x| x[0]).unwrap();
+    min[1] * min[2]
+}
+
+/// Stacks the layers of the picture, each pixel taking its colour from the topmost layer where
+/// it isn't transparent.
+fn decode(data: &[u8], width: usize, height: usize) -> Grid<u8> {
+    let mut image = Grid::new(width, height, 2);
+    let coords = image.coords().collect::<Vec<_>>();
+    for layer in data.chunks_exact(width * height).rev() {
+        for (coord, pixel) in coords.iter().zip(layer) {
+            if *pixel != b'2' {
+                image[*coord] = pixel - b'0';
+            }
+        }
+    }
+    image
+}
+
+fn part2(data: &[u8], width: usize, height: usize) -> String {
+    decode(data, width, height).render(|pixel| match pixel {
+        0 => ' ',
+        _ => 'x',
+    })
+}
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u32 = 8;
+
+    type Input = Vec<u8>;
+    type Part1 = usize;
+    type Part2 = String;
+
+    fn parse(source: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(source.trim().as_bytes().to_vec())
+    }
+
+    fn part1(data: &Vec<u8>) -> usize {
+        part1(data)
+    }
+
+    fn part2(data: &Vec<u8>) -> String {
+        part2(data, 25, 6)
+    }
+
+    fn picture(data: &Vec<u8>) -> Option<Image> {
+        let palette = Palette::mono();
+        let decoded = decode(data, 25, 6);
+        Some(Image::from_grid(&decoded, |pixel| {
+            palette.colour(i64::from(*pixel))
+        }))
+    }
+}
@@ -0,0 +1,35 @@
+use intcode::program::Program;
+use intcode::{Machine, PollExt};
+use std::error::Error;
+use util::Solution;
+
+/// Runs the BOOST program in the given mode, returning the keycode or coordinates it outputs.
+fn boost(mut machine: Machine, mode: i64) -> i64 {
+    machine.input(mode);
+    machine.map(|value| value.unwrap()).last().unwrap()
+}
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u32 = 9;
+
+    type Input = Machine;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(source: &str) -> Result<Machine, Box<dyn Error>> {
+        Ok(Program::parse(source)?.machine())
+    }
+
+    fn part1(machine: &Machine) -> i64 {
+        boost(machine.clone(), 1)
+    }
+
+    fn part2(machine: &Machine) -> i64 {
+        // only the sensor boost runs for long enough for caching blocks to pay off
+        let mut machine = machine.clone();
+        machine.set_caching(true);
+        boost(machine, 2)
+    }
+}
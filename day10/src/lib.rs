@@ -0,0 +1,352 @@
+use itertools::iproduct;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::f64;
+use util::geometry::{Point, Vector};
+use util::grid::{self, Grid};
+use util::Solution;
+
+#[cfg(test)]
+#[test]
+fn test_vector() {
+    assert_eq!(
+        VectorIter::new(Point::new(0, 4), Point::new(4, 2)).collect::<Vec<_>>(),
+        vec![Point::new(2, 3)]
+    );
+}
+
+struct VectorIter {
+    current: Point,
+    end: Option<Point>,
+    dimensions: Option<(usize, usize)>,
+    vector: Vector,
+}
+
+impl VectorIter {
+    fn new(start: Point, end: Point) -> VectorIter {
+        VectorIter {
+            current: start,
+            end: Some(end),
+            dimensions: None,
+            vector: (end - start).reduced(),
+        }
+    }
+
+    fn infinite(start: Point, vector: Vector, width: usize, height: usize) -> VectorIter {
+        VectorIter {
+            current: start,
+            end: None,
+            dimensions: Some((width, height)),
+            vector,
+        }
+    }
+}
+
+impl Iterator for VectorIter {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        let new = self.current + self.vector;
+        match (self.end, self.dimensions) {
+            (Some(end), _) if new == end => None,
+            (None, Some(dim))
+                if new.x < 0 || new.x >= dim.0 as i64 || new.y < 0 || new.y >= dim.1 as i64 =>
+            {
+                None
+            }
+            _ => {
+                self.current = new;
+                Some(self.current)
+            }
+        }
+    }
+}
+
+/// (0, -1) = 0
+/// (1, 0) = pi/2
+/// (0, 1) = pi
+/// (-1, 0) = 3pi/2
+fn vector_angle(vector: Vector) -> f64 {
+    let angle = (vector.x as f64).atan2(-(vector.y as f64));
+    if angle < 0f64 {
+        angle + (2f64 * f64::consts::PI)
+    } else {
+        angle
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_vector_angle() {
+    assert_eq!(vector_angle(Vector::new(0, -1)), 0f64);
+    assert_eq!(vector_angle(Vector::new(1, -1)), f64::consts::FRAC_PI_4);
+    assert_eq!(vector_angle(Vector::new(1, 0)), f64::consts::FRAC_PI_2);
+    assert_eq!(
+        vector_angle(Vector::new(1, 1)),
+        3f64 * f64::consts::FRAC_PI_4
+    );
+    assert_eq!(vector_angle(Vector::new(0, 1)), f64::consts::PI);
+    assert_eq!(
+        vector_angle(Vector::new(-1, 1)),
+        5f64 * f64::consts::FRAC_PI_4
+    );
+    assert_eq!(
+        vector_angle(Vector::new(-1, 0)),
+        3f64 * f64::consts::FRAC_PI_2
+    );
+    assert_eq!(
+        vector_angle(Vector::new(-1, -1)),
+        7f64 * f64::consts::FRAC_PI_4
+    );
+}
+
+fn parse_field(s: &str) -> Result<Grid<bool>, grid::ParseError> {
+    Grid::parse(s, |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })
+}
+
+trait Field: Sized {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn pos_iter(&self) -> Box<dyn Iterator<Item = Point>>;
+    fn asteroid(&self, pos: Point) -> bool;
+    fn is_empty(&self) -> bool;
+    fn best_position(&self) -> (Point, usize);
+    fn slopes(&self, start: Point) -> Vec<Vector>;
+}
+
+trait FieldMut: Field {
+    fn unset(&mut self, pos: Point);
+    fn vaporize(self, start: Point) -> Vaporize<Self>;
+}
+
+impl Field for Grid<bool> {
+    fn width(&self) -> usize {
+        Grid::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Grid::height(self)
+    }
+
+    fn pos_iter(&self) -> Box<dyn Iterator<Item = Point>> {
+        let (width, height) = (self.width() as i64, self.height() as i64);
+        Box::new(iproduct!(0..width, 0..height).map(|(x, y)| Point::new(x, y)))
+    }
+
+    fn asteroid(&self, pos: Point) -> bool {
+        self[pos.into()]
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos_iter().all(|pos| !self.asteroid(pos))
+    }
+
+    fn best_position(&self) -> (Point, usize) {
+        // useless hashing...
+        let mut visible = HashMap::new();
+
+        for start in self.pos_iter() {
+            if self.asteroid(start) {
+                visible.insert(
+                    start,
+                    self.pos_iter()
+                        .filter(|end| start != *end && self.asteroid(*end))
+                        .filter(|end| VectorIter::new(start, *end).all(|x| !self.asteroid(x)))
+                        .count(),
+                );
+            }
+        }
+
+        visible.into_iter().max_by_key(|&(_, count)| count).unwrap()
+    }
+
+    fn slopes(&self, start: Point) -> Vec<Vector> {
+        let slopes = self
+            .pos_iter()
+            .filter_map(|end| {
+                if start == end {
+                    None
+                } else {
+                    Some((end - start).reduced())
+                }
+            })
+            .collect::<HashSet<_>>();
+        let mut slopes = slopes.into_iter().collect::<Vec<_>>();
+        slopes.sort_by(|a, b| {
+            vector_angle(*a)
+                .partial_cmp(&vector_angle(*b))
+                .unwrap_or(Ordering::Equal)
+        });
+        slopes
+    }
+}
+
+impl FieldMut for Grid<bool> {
+    fn unset(&mut self, pos: Point) {
+        self[pos.into()] = false;
+    }
+
+    fn vaporize(mut self, start: Point) -> Vaporize<Self> {
+        self.unset(start);
+        Vaporize {
+            start,
+            slopes: self.slopes(start),
+            slopes_idx: 0,
+            field: self,
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test() {
+    let field = parse_field(
+        "......#.#.\n\
+         #..#.#....",
+    )
+    .unwrap();
+    assert_eq!(field.width(), 10);
+    assert_eq!(field.height(), 2);
+    assert!(!field.is_empty());
+    assert!(field.asteroid(Point::new(0, 1)));
+    assert!(!field.asteroid(Point::new(1, 0)));
+
+    let field = parse_field(
+        ".#..#\n\
+         .....\n\
+         #####\n\
+         ....#\n\
+         ...##",
+    )
+    .unwrap();
+    assert_eq!(field.best_position(), (Point::new(3, 4), 8));
+
+    let field = parse_field(
+        "......#.#.\n\
+         #..#.#....\n\
+         ..#######.\n\
+         .#.#.###..\n\
+         .#..#.....\n\
+         ..#....#.#\n\
+         #..#....#.\n\
+         .##.#..###\n\
+         ##...#..#.\n\
+         .#....####",
+    )
+    .unwrap();
+    assert_eq!(field.best_position(), (Point::new(5, 8), 33));
+
+    let field = parse_field(
+        "#.#...#.#.\n\
+         .###....#.\n\
+         .#....#...\n\
+         ##.#.#.#.#\n\
+         ....#.#.#.\n\
+         .##..###.#\n\
+         ..#...##..\n\
+         ..##....##\n\
+         ......#...\n\
+         .####.###.",
+    )
+    .unwrap();
+    assert_eq!(field.best_position(), (Point::new(1, 2), 35));
+
+    let field = parse_field(
+        ".#..##.###...#######\n\
+         ##.############..##.\n\
+         .#.######.########.#\n\
+         .###.#######.####.#.\n\
+         #####.##.#.##.###.##\n\
+         ..#####..#.#########\n\
+         ####################\n\
+         #.####....###.#.#.##\n\
+         ##.#################\n\
+         #####.##.###..####..\n\
+         ..######..##.#######\n\
+         ####.##.####...##..#\n\
+         .#####..#.######.###\n\
+         ##...#.##########...\n\
+         #.##########.#######\n\
+         .####.#.###.###.#.##\n\
+         ....##.##.###..#####\n\
+         .#.#.###########.###\n\
+         #.#.#.#####.####.###\n\
+         ###.##.####.##.#..##",
+    )
+    .unwrap();
+    assert_eq!(field.best_position(), (Point::new(11, 13), 210));
+    assert_eq!(
+        field.clone().vaporize(Point::new(11, 13)).next(),
+        Some(Point::new(11, 12))
+    );
+    assert_eq!(
+        field.clone().vaporize(Point::new(11, 13)).nth(1),
+        Some(Point::new(12, 1))
+    );
+    assert_eq!(
+        field.clone().vaporize(Point::new(11, 13)).nth(2),
+        Some(Point::new(12, 2))
+    );
+    assert_eq!(
+        field.clone().vaporize(Point::new(11, 13)).nth(199),
+        Some(Point::new(8, 2))
+    );
+}
+
+struct Vaporize<T: FieldMut> {
+    start: Point,
+    field: T,
+    slopes: Vec<Vector>,
+    slopes_idx: usize,
+}
+
+impl<T: FieldMut> Iterator for Vaporize<T> {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        if self.field.is_empty() {
+            None
+        } else {
+            loop {
+                let slope = self.slopes[self.slopes_idx];
+                self.slopes_idx = (self.slopes_idx + 1) % self.slopes.len();
+                if let Some(pos) =
+                    VectorIter::infinite(self.start, slope, self.field.width(), self.field.height())
+                        .find(|pos| self.field.asteroid(*pos))
+                {
+                    self.field.unset(pos);
+                    break Some(pos);
+                }
+            }
+        }
+    }
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u32 = 10;
+
+    type Input = Grid<bool>;
+    type Part1 = usize;
+    type Part2 = i64;
+
+    fn parse(source: &str) -> Result<Grid<bool>, Box<dyn Error>> {
+        Ok(parse_field(source)?)
+    }
+
+    fn part1(field: &Grid<bool>) -> usize {
+        field.best_position().1
+    }
+
+    fn part2(field: &Grid<bool>) -> i64 {
+        let (pos, _) = field.best_position();
+        let asteroid = field.clone().vaporize(pos).nth(199).unwrap();
+        asteroid.x * 100 + asteroid.y
+    }
+}
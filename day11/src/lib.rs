@@ -0,0 +1,94 @@
+use intcode::program::Program;
+use intcode::{PollExt, Runner};
+use std::error::Error;
+use std::io;
+use util::animate::{self, Animation, Colour};
+use util::geometry::{Direction, Point, Turn};
+use util::grid::Sparse;
+use util::image::{Image, Palette};
+use util::Solution;
+
+/// Runs the painting robot, calling `watch` with the panels painted so far and where the robot
+/// is after each panel it paints.
+fn run_robot(
+    input: &[i64],
+    start_color: i64,
+    mut watch: impl FnMut(&Sparse<i64>, Point),
+) -> Sparse<i64> {
+    let mut pos = Point::ORIGIN;
+    let mut dir = Direction::Up;
+    let mut map = Sparse::new();
+    map.insert(pos.into(), start_color);
+    let mut program = [0; 1280];
+    program[..input.len()].copy_from_slice(input);
+    let mut runner = Runner::new(&mut program);
+    loop {
+        // input current color
+        let input = map.get(pos.into()).copied().unwrap_or(0);
+        runner.input(input);
+        // output new color
+        let color = match runner.next() {
+            Some(x) => x.unwrap(),
+            None => break,
+        };
+        map.insert(pos.into(), color);
+        watch(&map, pos);
+        // output direction
+        let turn = runner.next().unwrap().unwrap();
+        dir = dir.turn(Turn::from_intcode(turn).unwrap());
+        pos += dir.vector();
+    }
+    map
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u32 = 11;
+
+    type Input = Vec<i64>;
+    type Part1 = usize;
+    type Part2 = String;
+
+    fn parse(source: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+        Ok(Program::parse(source)?.image())
+    }
+
+    fn part1(input: &Vec<i64>) -> usize {
+        run_robot(input, 0, |_, _| {}).len()
+    }
+
+    fn part2(input: &Vec<i64>) -> String {
+        let map = run_robot(input, 1, |_, _| {});
+        map.render(|_, color| match color {
+            None | Some(0) => ' ',
+            Some(1) => '#',
+            _ => unreachable!(),
+        })
+    }
+
+    fn picture(input: &Vec<i64>) -> Option<Image> {
+        let map = run_robot(input, 1, |_, _| {});
+        let palette = Palette::mono();
+        Some(Image::from_sparse(&map, |color| {
+            palette.colour(*color.unwrap_or(&0))
+        }))
+    }
+
+    /// Watches the robot paint the registration identifier.
+    fn animate(input: &Vec<i64>, animation: &mut Animation) -> io::Result<()> {
+        let mut result = Ok(());
+        run_robot(input, 1, |map, robot| {
+            if result.is_ok() {
+                let picture = map.render(|pos, color| match color {
+                    _ if pos == robot.into() => '@',
+                    Some(1) => '#',
+                    _ => ' ',
+                });
+                let picture = animate::paint(&picture, |c| Some(Colour::Red).filter(|_| c == '@'));
+                result = animation.frame(&picture);
+            }
+        });
+        result
+    }
+}
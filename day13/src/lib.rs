@@ -0,0 +1,149 @@
+use intcode::program::Program;
+use intcode::session::{Recordable, Recorder, Session};
+use intcode::{Machine, PollExt};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::io;
+use std::task::Poll;
+use util::animate::{self, Animation, Colour};
+use util::grid::{Coord, Sparse};
+use util::Solution;
+
+#[derive(Default)]
+struct State {
+    board: Sparse<i64>,
+    score: i64,
+}
+
+impl State {
+    fn is_empty(&self) -> bool {
+        !self.board.iter().any(|(_, id)| *id == 2)
+    }
+
+    fn paddle(&self) -> Coord {
+        self.board.iter().find(|(_, id)| **id == 3).unwrap().0
+    }
+
+    fn ball(&self) -> Coord {
+        self.board.iter().find(|(_, id)| **id == 4).unwrap().0
+    }
+}
+
+fn update_state(machine: &mut impl Iterator<Item = Poll<i64>>, state: &mut State) {
+    while let Some(Poll::Ready(x)) = machine.next() {
+        let y = machine.next().unwrap().unwrap();
+        let id = machine.next().unwrap().unwrap();
+        if (x, y) == (-1, 0) {
+            state.score = id;
+        } else {
+            state.board.insert((x, y), id);
+        }
+    }
+}
+
+/// Draws the score and the board, for watching the game.
+fn draw_state(state: &State) -> String {
+    let board = state.board.render(|_, id| match id {
+        None | Some(0) => ' ',
+        Some(1) => '#',
+        Some(2) => 'B',
+        Some(3) => '-',
+        Some(4) => '*',
+        _ => unreachable!(),
+    });
+    let board = animate::paint(&board, |c| match c {
+        'B' => Some(Colour::Blue),
+        '-' => Some(Colour::Yellow),
+        '*' => Some(Colour::Red),
+        _ => None,
+    });
+    format!("score: {}\n{}", state.score, board)
+}
+
+/// Plays the game with coins in until every block is broken, keeping the paddle under the ball,
+/// and returns the final score. `watch` is shown the state before each move and at the end.
+fn play(machine: &mut impl Recordable, mut watch: impl FnMut(&State)) -> i64 {
+    let mut state = State::default();
+    update_state(machine, &mut state);
+    while !state.is_empty() {
+        watch(&state);
+        machine.input(match state.ball().0.cmp(&state.paddle().0) {
+            Ordering::Less => -1,
+            Ordering::Greater => 1,
+            Ordering::Equal => 0,
+        });
+        update_state(machine, &mut state);
+    }
+    watch(&state);
+    state.score
+}
+
+/// Plays the game with coins in, recording a session of it to replay later.
+pub fn record(machine: &Machine) -> Session {
+    let mut machine = machine.clone();
+    machine.reset_with(&[(0, 2)]); // coins
+    let mut machine = Recorder::new(machine);
+    play(&mut machine, |_| {});
+    machine.into_session()
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u32 = 13;
+
+    type Input = Machine;
+    type Part1 = usize;
+    type Part2 = i64;
+
+    fn parse(source: &str) -> Result<Machine, Box<dyn Error>> {
+        let mut machine = Program::parse(source)?.machine();
+        machine.set_caching(true);
+        Ok(machine)
+    }
+
+    fn part1(machine: &Machine) -> usize {
+        let mut machine = machine.clone();
+        let mut state = State::default();
+        update_state(&mut machine, &mut state);
+        state.board.iter().filter(|(_, id)| **id == 2).count()
+    }
+
+    fn part2(machine: &Machine) -> i64 {
+        let mut machine = machine.clone();
+        machine.reset_with(&[(0, 2)]); // coins
+        play(&mut machine, |_| {})
+    }
+
+    /// Watches the game being played.
+    fn animate(machine: &Machine, animation: &mut Animation) -> io::Result<()> {
+        let mut machine = machine.clone();
+        machine.reset_with(&[(0, 2)]); // coins
+        let mut result = Ok(());
+        play(&mut machine, |state| {
+            if result.is_ok() {
+                result = animation.frame(&draw_state(state));
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_replay() {
+    use util::Input;
+
+    // a whole game, kept in session.txt; run with RECORD=1 to record it again if the way the
+    // paddle is played changes
+    let machine = Day13::parse(&Input::Default.read().unwrap()).unwrap();
+    if std::env::var_os("RECORD").is_some() {
+        util::write_session("session.txt", &record(&machine)).unwrap();
+    }
+    let session = util::read_session("session.txt").unwrap();
+    let mut replay = machine.clone();
+    replay.reset_with(&[(0, 2)]); // coins
+    assert_eq!(session.replay(&mut replay), Ok(()));
+    assert_eq!(replay.next(), None);
+    assert_eq!(record(&machine), session);
+}
@@ -0,0 +1,304 @@
+#![deny(rust_2018_idioms)]
+
+use num_integer::Integer;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::ops::{DivAssign, MulAssign};
+use util::parse::{self, Cursor, ParseError};
+use util::Solution;
+
+type Reactions<'a> = HashMap<&'a str, Reaction<'a>>;
+
+#[derive(Debug, Clone)]
+struct Reaction<'a> {
+    inputs: Vec<Value<'a>>,
+    output: Value<'a>,
+}
+
+impl<'a> Reaction<'a> {
+    /// Reads a reaction like `7 A, 1 E => 1 FUEL`.
+    fn parse(line: &mut Cursor<'a>) -> Result<Reaction<'a>, ParseError> {
+        let inputs = line.sep_by(", ", Value::parse)?;
+        line.literal(" => ")?;
+        Ok(Reaction {
+            inputs,
+            output: Value::parse(line)?,
+        })
+    }
+
+    fn flatten(&mut self) {
+        let mut map = HashMap::new();
+        for input in &self.inputs {
+            map.entry(input.unit)
+                .and_modify(|v| *v += input.amount)
+                .or_insert(input.amount);
+        }
+        let mut inputs = Vec::new();
+        for input in &self.inputs {
+            if let Some(amount) = map.remove(input.unit) {
+                inputs.push(Value {
+                    amount,
+                    unit: input.unit,
+                });
+            }
+        }
+        self.inputs = inputs;
+
+        self.div_assign(
+            self.inputs
+                .iter()
+                .map(|input| input.amount)
+                .fold(self.output.amount, |a, b| a.gcd(&b)),
+        );
+    }
+}
+
+impl Display for Reaction<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for input in &self.inputs {
+            if !first {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", input)?;
+            first = false;
+        }
+        write!(f, " => {}", self.output)
+    }
+}
+
+impl<'a> MulAssign<u64> for Reaction<'a> {
+    fn mul_assign(&mut self, rhs: u64) {
+        for input in self.inputs.iter_mut() {
+            *input *= rhs;
+        }
+        self.output *= rhs;
+    }
+}
+
+impl<'a> DivAssign<u64> for Reaction<'a> {
+    fn div_assign(&mut self, rhs: u64) {
+        for input in self.inputs.iter_mut() {
+            *input /= rhs;
+        }
+        self.output /= rhs;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Value<'a> {
+    amount: u64,
+    unit: &'a str,
+}
+
+impl<'a> Value<'a> {
+    fn parse(value: &mut Cursor<'a>) -> Result<Value<'a>, ParseError> {
+        let amount = value.int()?;
+        value.literal(" ")?;
+        Ok(Value {
+            amount,
+            unit: value.ident()?,
+        })
+    }
+}
+
+impl Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.unit)
+    }
+}
+
+impl<'a> MulAssign<u64> for Value<'a> {
+    fn mul_assign(&mut self, rhs: u64) {
+        self.amount *= rhs;
+    }
+}
+
+impl<'a> DivAssign<u64> for Value<'a> {
+    fn div_assign(&mut self, rhs: u64) {
+        self.amount /= rhs;
+    }
+}
+
+fn parse_reactions(input: &str) -> Result<Reactions<'_>, ParseError> {
+    let rxns = parse::lines(input, Reaction::parse)?;
+    Ok(rxns.into_iter().map(|rxn| (rxn.output.unit, rxn)).collect())
+}
+
+fn ore_for_fuel(rxns: &Reactions<'_>) -> u64 {
+    ore_for(rxns, 1)
+}
+
+fn ore_for(rxns: &Reactions<'_>, fuel: u64) -> u64 {
+    let mut required = HashMap::new();
+    required.insert("FUEL", fuel as i64);
+    loop {
+        let units = required
+            .iter()
+            .filter_map(|(unit, amount)| {
+                if *unit == "ORE" || *amount <= 0 {
+                    None
+                } else {
+                    Some(*unit)
+                }
+            })
+            .collect::<Vec<_>>();
+        if units.is_empty() {
+            break *required.get("ORE").unwrap() as u64;
+        }
+        let mut extra = HashMap::new();
+        for unit in units {
+            let amount = required.get_mut(unit).unwrap();
+            let rxn = rxns.get(&unit).unwrap();
+            let factor = (*amount / rxn.output.amount as i64)
+                + if (*amount % rxn.output.amount as i64) == 0 {
+                    0
+                } else {
+                    1
+                };
+            match (rxn.output.amount as i64 * factor) - *amount {
+                0 => {}
+                e => {
+                    extra.insert(unit, e);
+                }
+            }
+            *amount = 0;
+            for input in &rxn.inputs {
+                let amount = input.amount as i64 * factor;
+                required
+                    .entry(input.unit)
+                    .and_modify(|v| *v += amount)
+                    .or_insert(amount);
+            }
+        }
+        for (unit, extra) in extra {
+            *required.get_mut(unit).unwrap() -= extra;
+        }
+    }
+}
+
+/// The most fuel that can be made from `input_ore`, starting from the estimate of
+/// [`ore_for_fuel_exact`] and checking it against [`ore_for`].
+fn max_fuel(rxns: &Reactions<'_>, input_ore: u64) -> u64 {
+    let mut fuel = ore_for_fuel_exact(rxns, input_ore);
+    while fuel > 0 && ore_for(rxns, fuel) > input_ore {
+        fuel -= 1;
+    }
+    while ore_for(rxns, fuel + 1) <= input_ore {
+        fuel += 1;
+    }
+    fuel
+}
+
+fn ore_for_fuel_exact(rxns: &Reactions<'_>, input_ore: u64) -> u64 {
+    let mut rxns = rxns.clone();
+    for rxn in rxns.values_mut() {
+        rxn.flatten();
+    }
+
+    while let Some(rxn) = rxns.values().find(|rxn| {
+        rxn.inputs.len() == 1 && rxn.inputs[0].unit == "ORE" && rxn.output.unit != "FUEL"
+    }) {
+        // {ore} ORE => {amount} {unit}
+        let ore = rxn.inputs[0].amount;
+        let amount = rxn.output.amount;
+        let unit = rxn.output.unit;
+
+        for rxn in rxns
+            .values_mut()
+            .filter(|rxn| rxn.inputs.iter().any(|input| input.unit == unit))
+        {
+            // {other} _, ... => _ {unit}
+            let other = rxn
+                .inputs
+                .iter()
+                .find(|input| input.unit == unit)
+                .unwrap()
+                .amount;
+            let lcm = amount.lcm(&other);
+            *rxn *= lcm / other;
+            let input = rxn
+                .inputs
+                .iter_mut()
+                .find(|input| input.unit == unit)
+                .unwrap();
+            *input = Value {
+                amount: lcm / amount * ore,
+                unit: "ORE",
+            };
+            rxn.flatten();
+        }
+
+        rxns.remove(unit);
+    }
+
+    let rxn = rxns.get("FUEL").unwrap();
+    assert!(rxn.inputs.len() == 1 && rxn.inputs[0].unit == "ORE");
+    let ore = rxn.inputs[0].amount;
+    let amount = rxn.output.amount;
+    (input_ore as f64 / ore as f64 * amount as f64) as u64
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u32 = 14;
+
+    // the reactions borrow their names from the input, so keep the input and parse it again for
+    // each part
+    type Input = String;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(source: &str) -> Result<String, Box<dyn Error>> {
+        parse_reactions(source)?;
+        Ok(source.to_owned())
+    }
+
+    fn part1(input: &String) -> u64 {
+        ore_for_fuel(&parse_reactions(input).unwrap())
+    }
+
+    fn part2(input: &String) -> u64 {
+        max_fuel(&parse_reactions(input).unwrap(), 1000000000000)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test() {
+    let input = "9 ORE => 2 A
+                 8 ORE => 3 B
+                 7 ORE => 5 C
+                 3 A, 4 B => 1 AB
+                 5 B, 7 C => 1 BC
+                 4 C, 1 A => 1 CA
+                 2 AB, 3 BC, 4 CA => 1 FUEL";
+    let rxns = parse_reactions(input).unwrap();
+    assert_eq!(ore_for_fuel(&rxns), 165);
+
+    let input = "171 ORE => 8 CNZTR
+                 7 ZLQW, 3 BMBT, 9 XCVML, 26 XMNCP, 1 WPTQ, 2 MZWV, 1 RJRHP => 4 PLWSL
+                 114 ORE => 4 BHXH
+                 14 VRPVC => 6 BMBT
+                 6 BHXH, 18 KTJDG, 12 WPTQ, 7 PLWSL, 31 FHTLT, 37 ZDVW => 1 FUEL
+                 6 WPTQ, 2 BMBT, 8 ZLQW, 18 KTJDG, 1 XMNCP, 6 MZWV, 1 RJRHP => 6 FHTLT
+                 15 XDBXC, 2 LTCX, 1 VRPVC => 6 ZLQW
+                 13 WPTQ, 10 LTCX, 3 RJRHP, 14 XMNCP, 2 MZWV, 1 ZLQW => 1 ZDVW
+                 5 BMBT => 4 WPTQ
+                 189 ORE => 9 KTJDG
+                 1 MZWV, 17 XDBXC, 3 XCVML => 2 XMNCP
+                 12 VRPVC, 27 CNZTR => 2 XDBXC
+                 15 KTJDG, 12 BHXH => 5 XCVML
+                 3 BHXH, 2 VRPVC => 7 MZWV
+                 121 ORE => 7 VRPVC
+                 7 XCVML => 6 RJRHP
+                 5 BHXH, 4 VRPVC => 5 LTCX";
+    let rxns = parse_reactions(input).unwrap();
+    assert_eq!(ore_for_fuel(&rxns), 2210736);
+    assert_eq!(ore_for_fuel_exact(&rxns, 1000000000000), 460664);
+    assert_eq!(max_fuel(&rxns, 1000000000000), 460664);
+    assert!(ore_for(&rxns, 460664) <= 1000000000000);
+    assert!(ore_for(&rxns, 460665) > 1000000000000);
+}
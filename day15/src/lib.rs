@@ -0,0 +1,145 @@
+use intcode::program::Program;
+use intcode::session::{Recorder, Session};
+use intcode::{Machine, PollExt};
+use rand::Rng;
+use std::error::Error;
+use std::io;
+use util::animate::{self, Animation, Colour};
+use util::geometry::{Direction, Point};
+use util::grid::{self, Coord, Sparse};
+use util::search;
+use util::Solution;
+
+/// Wanders at random until the droid finds the oxygen system or has made `moves` moves, returning
+/// the map it saw and the session that drove it there, so the same walk can be replayed later.
+#[allow(unused)]
+fn flail(machine: Machine, rng: &mut impl Rng, moves: usize) -> (Sparse<i64>, Session) {
+    let mut machine = Recorder::new(machine);
+    let mut map = Sparse::new();
+    let mut pos = Point::ORIGIN;
+    map.insert(pos.into(), 1);
+
+    for _ in 0..moves {
+        let dir = Direction::ALL[rng.gen_range(0, 4)];
+        machine.input(dir.to_intcode());
+        let new_pos = pos + dir.vector();
+        let output = machine.next().unwrap().unwrap();
+        map.insert(new_pos.into(), output);
+        match output {
+            1 => {
+                pos = new_pos;
+            }
+            2 => {
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    (map, machine.into_session())
+}
+
+#[allow(unused)]
+fn print_map(map: &Sparse<i64>) {
+    print!(
+        "{}",
+        map.render(|coord, tile| match tile {
+            _ if coord == (0, 0) => 'x',
+            Some(0) => '#',
+            Some(1) => ' ',
+            Some(2) => 'D',
+            Some(3) => '*',
+            None => '.',
+            _ => unreachable!(),
+        })
+    );
+}
+
+/// Walks the droid down every passage it hasn't been down yet, backing up at each dead end,
+/// until it has seen the whole area. The droid ends up back at `pos`.
+fn explore(machine: &mut Machine, map: &mut Sparse<i64>, pos: Point) {
+    for dir in Direction::ALL {
+        let next = pos + dir.vector();
+        if map.get(next.into()).is_some() {
+            continue;
+        }
+        machine.input(dir.to_intcode());
+        let status = machine.next().unwrap().unwrap();
+        map.insert(next.into(), status);
+        if status != 0 {
+            explore(machine, map, next);
+            machine.input(dir.reverse().to_intcode());
+            machine.next().unwrap().unwrap();
+        }
+    }
+}
+
+/// The tiles next to `coord` that the droid can move to.
+fn open(map: &Sparse<i64>, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+    grid::neighbours4(coord).filter(move |adj| map.get(*adj).is_some_and(|tile| *tile != 0))
+}
+
+/// The tiles the oxygen spreads to each minute, starting with the oxygen system's own.
+fn spread(map: &Sparse<i64>) -> Vec<Vec<Coord>> {
+    let (oxygen, _) = map.iter().find(|(_, tile)| **tile == 2).unwrap();
+    search::flood(oxygen, |coord| open(map, *coord))
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u32 = 15;
+
+    type Input = Sparse<i64>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(source: &str) -> Result<Sparse<i64>, Box<dyn Error>> {
+        let mut machine = Machine::with_memory(Program::parse(source)?.image(), 2048);
+        let mut map = Sparse::new();
+        map.insert((0, 0), 1);
+        explore(&mut machine, &mut map, Point::ORIGIN);
+        Ok(map)
+    }
+
+    fn part1(map: &Sparse<i64>) -> usize {
+        let path = search::bfs((0, 0), |coord| open(map, *coord), |coord| map[*coord] == 2);
+        path.unwrap().len() - 1
+    }
+
+    fn part2(map: &Sparse<i64>) -> usize {
+        spread(map).len() - 1
+    }
+
+    /// Watches the oxygen fill the area a minute at a time.
+    fn animate(map: &Sparse<i64>, animation: &mut Animation) -> io::Result<()> {
+        let mut map = map.clone();
+        for minute in spread(&map) {
+            map.extend(minute.into_iter().map(|coord| (coord, 2)));
+            let picture = map.render(|_, tile| match tile {
+                Some(0) => '#',
+                Some(2) => 'O',
+                _ => ' ',
+            });
+            let picture = animate::paint(&picture, |c| Some(Colour::Cyan).filter(|_| c == 'O'));
+            animation.frame(&picture)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_flail() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use util::Input;
+
+    let machine = Machine::with_memory(Input::Default.program().unwrap().image(), 2048);
+    let (map, session) = flail(machine.clone(), &mut StdRng::seed_from_u64(15), 500);
+    assert_eq!(session.events().len(), 1000);
+    assert!(map.len() > 1);
+    assert_eq!(session.replay(&mut machine.clone()), Ok(()));
+    let session = Session::parse(&session.to_string()).unwrap();
+    assert_eq!(session.replay(&mut machine.clone()), Ok(()));
+}
@@ -0,0 +1,71 @@
+use intcode::program::Program;
+use intcode::Runner;
+use std::error::Error;
+use std::task::Poll;
+use util::grid::Grid;
+use util::Solution;
+
+/// Reads the picture the cameras draw of the scaffolding.
+fn read_map(runner: &mut Runner) -> Grid<char> {
+    let mut s = String::new();
+    while let Some(Poll::Ready(n)) = runner.next() {
+        s.push(char::from(n as u8));
+    }
+    Grid::parse(&s, Some).unwrap()
+}
+
+fn alignment(input: &[i64]) -> i64 {
+    let mut program = [0; 8192];
+    program[..input.len()].copy_from_slice(input);
+    let mut runner = Runner::new(&mut program);
+    let map = read_map(&mut runner);
+    map.iter()
+        .filter(|(coord, tile)| {
+            **tile == '#'
+                && map
+                    .neighbours4(*coord)
+                    .filter(|adj| map[*adj] == '#')
+                    .count()
+                    == 4
+        })
+        .map(|((x, y), _)| x * y)
+        .sum()
+}
+
+fn dust(input: &[i64]) -> i64 {
+    // it'd be neat to figure this out programatically but my brain works just as well!
+    let a = "L,8,R,10,L,10";
+    let b = "R,10,L,8,L,8,L,10";
+    let c = "L,4,L,6,L,8,L,8";
+    let main = "A,B,A,C,B,C,A,C,B,C";
+
+    let mut program = [0; 8192];
+    program[..input.len()].copy_from_slice(input);
+    program[0] = 2;
+    Runner::new(&mut program)
+        .full_input(format!("{}\n{}\n{}\n{}\nn\n", main, a, b, c).into_bytes())
+        .last()
+        .unwrap()
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u32 = 17;
+
+    type Input = Vec<i64>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(source: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+        Ok(Program::parse(source)?.image())
+    }
+
+    fn part1(input: &Vec<i64>) -> i64 {
+        alignment(input)
+    }
+
+    fn part2(input: &Vec<i64>) -> i64 {
+        dust(input)
+    }
+}
@@ -0,0 +1,346 @@
+//! Coverage-guided fuzzing of the input space of intcode programs.
+//!
+//! The program is the target: each run feeds it a sequence of inputs through `Runner::input`
+//! until it halts, faults, runs out of fuel, or asks for more input than the sequence has. Input
+//! sequences that reach addresses no earlier run executed, or that make the program output a
+//! value it hasn't output before, are kept in the corpus and mutated to make new ones.
+
+use intcode::decode::{Instruction, Mode, Opcode};
+use intcode::{Fault, Runner, Step};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Instructions to execute before deciding a run isn't going to terminate.
+    pub fuel: usize,
+    /// The longest input sequence to try.
+    pub max_inputs: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            fuel: 100_000,
+            max_inputs: 64,
+        }
+    }
+}
+
+/// Why a run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    Halted,
+    /// The program wanted more input than it was given. This is how most runs of an interactive
+    /// program end.
+    Blocked,
+    /// The program was still going when it ran out of fuel, at this address.
+    Timeout(usize),
+    Fault(Fault),
+}
+
+#[derive(Debug, Clone)]
+pub struct Run {
+    /// The inputs the program actually consumed.
+    pub inputs: Vec<i64>,
+    pub outputs: Vec<i64>,
+    /// Which addresses were executed as the start of an instruction.
+    pub coverage: Vec<bool>,
+    pub end: End,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finding {
+    Fault { inputs: Vec<i64>, fault: Fault },
+    Timeout { inputs: Vec<i64>, ip: usize },
+    Output { inputs: Vec<i64>, value: i64 },
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Finding::Fault { inputs, fault } => write!(f, "fault: {} <- {:?}", fault, inputs),
+            Finding::Timeout { inputs, ip } => {
+                write!(f, "no termination (ip={}) <- {:?}", ip, inputs)
+            }
+            Finding::Output { inputs, value } => write!(f, "new output {} <- {:?}", value, inputs),
+        }
+    }
+}
+
+pub struct Fuzzer {
+    program: Vec<i64>,
+    config: Config,
+    rng: StdRng,
+    dictionary: Vec<i64>,
+    corpus: Vec<Vec<i64>>,
+    coverage: Vec<bool>,
+    outputs: HashSet<i64>,
+    faults: HashSet<usize>,
+    timeouts: HashSet<usize>,
+    findings: Vec<Finding>,
+    runs: usize,
+}
+
+impl Fuzzer {
+    /// Creates a fuzzer for `program`, which should already be padded out to the amount of memory
+    /// it needs.
+    pub fn new(program: Vec<i64>, config: Config, seed: u64) -> Fuzzer {
+        let dictionary = dictionary(&program);
+        let coverage = vec![false; program.len()];
+        let mut fuzzer = Fuzzer {
+            program,
+            config,
+            rng: StdRng::seed_from_u64(seed),
+            dictionary,
+            corpus: Vec::new(),
+            coverage,
+            outputs: HashSet::new(),
+            faults: HashSet::new(),
+            timeouts: HashSet::new(),
+            findings: Vec::new(),
+            runs: 0,
+        };
+        fuzzer.evaluate(Vec::new());
+        fuzzer
+    }
+
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    pub fn corpus(&self) -> &[Vec<i64>] {
+        &self.corpus
+    }
+
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    /// The number of addresses executed by any run so far.
+    pub fn covered(&self) -> usize {
+        self.coverage.iter().filter(|c| **c).count()
+    }
+
+    /// Runs the program on `inputs`.
+    pub fn execute(&self, inputs: &[i64]) -> Run {
+        let mut memory = self.program.clone();
+        let mut runner = Runner::new(&mut memory);
+        let mut coverage = vec![false; self.program.len()];
+        let mut consumed = 0;
+        let mut outputs = Vec::new();
+        let mut fuel = self.config.fuel;
+        let end = loop {
+            if fuel == 0 {
+                break End::Timeout(runner.ip());
+            }
+            let ip = runner.ip();
+            match runner.try_step() {
+                Ok(Step::Continue) => {}
+                Ok(Step::Output(value)) => outputs.push(value),
+                Ok(Step::Blocked) => match inputs.get(consumed) {
+                    Some(input) => {
+                        runner.input(*input);
+                        consumed += 1;
+                        continue;
+                    }
+                    None => break End::Blocked,
+                },
+                Ok(Step::Halted) => {
+                    coverage[ip] = true;
+                    break End::Halted;
+                }
+                Err(fault) => break End::Fault(fault),
+            }
+            coverage[ip] = true;
+            fuel -= 1;
+        };
+        Run {
+            inputs: inputs[..consumed].to_vec(),
+            outputs,
+            coverage,
+            end,
+        }
+    }
+
+    /// Mutates an input sequence from the corpus and runs it, returning anything new it found.
+    pub fn step(&mut self) -> &[Finding] {
+        let inputs = self
+            .corpus
+            .choose(&mut self.rng)
+            .cloned()
+            .unwrap_or_default();
+        let inputs = self.mutate(inputs);
+        self.evaluate(inputs)
+    }
+
+    pub fn fuzz(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.step();
+        }
+    }
+
+    fn evaluate(&mut self, inputs: Vec<i64>) -> &[Finding] {
+        let run = self.execute(&inputs);
+        self.runs += 1;
+        let found = self.findings.len();
+
+        let mut interesting = false;
+        for (seen, new) in self.coverage.iter_mut().zip(&run.coverage) {
+            if *new && !*seen {
+                *seen = true;
+                interesting = true;
+            }
+        }
+        for value in &run.outputs {
+            if self.outputs.insert(*value) {
+                interesting = true;
+                self.findings.push(Finding::Output {
+                    inputs: run.inputs.clone(),
+                    value: *value,
+                });
+            }
+        }
+        match run.end {
+            End::Fault(fault) if self.faults.insert(fault.ip) => {
+                self.findings.push(Finding::Fault {
+                    inputs: run.inputs.clone(),
+                    fault,
+                });
+            }
+            End::Timeout(ip) if self.timeouts.insert(ip) => {
+                self.findings.push(Finding::Timeout {
+                    inputs: run.inputs.clone(),
+                    ip,
+                });
+            }
+            _ => {}
+        }
+        // Runs that don't terminate are too slow to build on.
+        if (interesting || self.corpus.is_empty()) && !matches!(run.end, End::Timeout(_)) {
+            self.corpus.push(run.inputs);
+        }
+
+        &self.findings[found..]
+    }
+
+    fn value(&mut self) -> i64 {
+        if self.rng.gen_bool(0.8) {
+            *self.dictionary.choose(&mut self.rng).unwrap()
+        } else {
+            self.rng.gen_range(-1000, 1000)
+        }
+    }
+
+    fn mutate(&mut self, mut inputs: Vec<i64>) -> Vec<i64> {
+        for _ in 0..self.rng.gen_range(1, 5) {
+            let len = inputs.len();
+            match self.rng.gen_range(0, 6) {
+                0 if len > 0 => {
+                    let i = self.rng.gen_range(0, len);
+                    inputs[i] = self.value();
+                }
+                1 if len > 0 => {
+                    let i = self.rng.gen_range(0, len);
+                    // the dictionary has the extremes in it, so this has to wrap rather than overflow
+                    inputs[i] = inputs[i].wrapping_add(if self.rng.gen() { 1 } else { -1 });
+                }
+                2 if len > 0 => {
+                    inputs.remove(self.rng.gen_range(0, len));
+                }
+                3 => {
+                    let value = self.value();
+                    inputs.insert(self.rng.gen_range(0, len + 1), value);
+                }
+                4 => {
+                    // splice the start of this one onto the end of another
+                    if let Some(other) = self.corpus.choose(&mut self.rng) {
+                        let at = self.rng.gen_range(0, len + 1);
+                        let from = self.rng.gen_range(0, other.len() + 1);
+                        inputs.truncate(at);
+                        inputs.extend_from_slice(&other[from..]);
+                    }
+                }
+                _ => {
+                    let value = self.value();
+                    inputs.push(value);
+                }
+            }
+        }
+        inputs.truncate(self.config.max_inputs);
+        inputs
+    }
+}
+
+/// Collects values worth trying as inputs: small numbers, plus anything the program compares
+/// against.
+fn dictionary(program: &[i64]) -> Vec<i64> {
+    let mut values = (-1..=4).collect::<HashSet<_>>();
+    for addr in 0..program.len() {
+        if let Ok(instruction) = Instruction::decode(program, addr) {
+            if let Opcode::Equals | Opcode::LessThan = instruction.opcode {
+                for param in &instruction.params()[..2] {
+                    if param.mode == Mode::Immediate {
+                        values.insert(param.value);
+                        values.insert(param.value.saturating_add(1));
+                        values.insert(param.value.saturating_sub(1));
+                    }
+                }
+            }
+        }
+    }
+    let mut values = values.into_iter().collect::<Vec<_>>();
+    values.sort_unstable();
+    values
+}
+
+#[cfg(test)]
+#[test]
+fn test() {
+    // faults only if the inputs are 17 then 3, and loops forever on a first input of 5
+    let mut program = vec![
+        3, 100, // in [100]
+        1008, 100, 17, 101, // eq [100], 17, [101]
+        1006, 101, 20, // jf [101], 20
+        3, 100, // in [100]
+        1008, 100, 3, 101, // eq [100], 3, [101]
+        1006, 101, 30, // jf [101], 30
+        42, 99, // illegal instruction
+        1008, 100, 5, 101, // 20: eq [100], 5, [101]
+        1005, 101, 24, // 24: jt [101], 24
+        4, 100, 99, // 27: out [100]
+        99, // 30: hlt
+    ];
+    program.resize(102, 0);
+
+    let config = Config {
+        fuel: 1000,
+        ..Config::default()
+    };
+    let mut fuzzer = Fuzzer::new(program, config, 2019);
+    fuzzer.fuzz(2000);
+    let findings = fuzzer.findings();
+    assert!(findings.contains(&Finding::Fault {
+        inputs: vec![17, 3],
+        fault: Fault {
+            ip: 18,
+            kind: intcode::FaultKind::IllegalInstruction(42),
+        },
+    }));
+    assert!(findings.contains(&Finding::Timeout {
+        inputs: vec![5],
+        ip: 24,
+    }));
+    assert!(findings
+        .iter()
+        .any(|finding| matches!(finding, Finding::Output { value: 16, .. })));
+    assert_eq!(fuzzer.covered(), 11);
+
+    // nudging the extremes the dictionary seeds wraps around rather than overflowing
+    for _ in 0..1000 {
+        fuzzer.mutate(vec![i64::MAX, i64::MIN]);
+    }
+}
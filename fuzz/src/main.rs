@@ -0,0 +1,63 @@
+use fuzz::{Config, Fuzzer};
+use intcode::program::Program;
+use std::env;
+use std::process;
+
+const USAGE: &str = "usage: fuzz PROGRAM [--iterations N] [--seed N] [--memory WORDS] \
+                     [--fuel N] [--max-inputs N]";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| usage());
+    let mut config = Config::default();
+    let mut iterations = 100_000;
+    let mut seed = 0;
+    let mut memory = 4096;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_else(|| usage())
+        };
+        match arg.as_str() {
+            "--iterations" => iterations = value(),
+            "--seed" => seed = value() as u64,
+            "--memory" => memory = value(),
+            "--fuel" => config.fuel = value(),
+            "--max-inputs" => config.max_inputs = value(),
+            _ => usage(),
+        }
+    }
+
+    let input = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        eprintln!("{}: {}", path, err);
+        process::exit(1);
+    });
+    let program = Program::parse(&input).unwrap_or_else(|err| {
+        eprintln!("{}:{}", path, err);
+        process::exit(1);
+    });
+    let mut image = program.image();
+    image.resize(program.memory_size().max(memory), 0);
+
+    let mut fuzzer = Fuzzer::new(image, config, seed);
+    for finding in fuzzer.findings() {
+        println!("{}", finding);
+    }
+    for _ in 0..iterations {
+        for finding in fuzzer.step() {
+            println!("{}", finding);
+        }
+    }
+    println!(
+        "{} runs, {} inputs in corpus, {} addresses covered",
+        fuzzer.runs(),
+        fuzzer.corpus().len(),
+        fuzzer.covered()
+    );
+}
@@ -0,0 +1,97 @@
+use intcode::inspect::{self, Trace};
+use intcode::program::Program;
+use intcode::{Runner, Step};
+use std::env;
+use std::process;
+use std::str::FromStr;
+
+const USAGE: &str = "usage: inspect PROGRAM [--input N]... [--memory WORDS] [--fuel N]\n       \
+                     inspect --diff BEFORE AFTER";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+fn value<T: FromStr>(args: &mut impl Iterator<Item = String>) -> T {
+    args.next()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(|| usage())
+}
+
+fn load(path: &str) -> Program {
+    let source = std::fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("{}: {}", path, err);
+        process::exit(1);
+    });
+    Program::parse(&source).unwrap_or_else(|err| {
+        eprintln!("{}:{}", path, err);
+        process::exit(1);
+    })
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| usage());
+    if path == "--diff" {
+        let before = load(&args.next().unwrap_or_else(|| usage())).image();
+        let after = load(&args.next().unwrap_or_else(|| usage())).image();
+        for change in inspect::diff(&before, &after) {
+            println!("{}", change);
+        }
+        return;
+    }
+
+    let mut inputs = Vec::new();
+    let mut memory = 0;
+    let mut fuel = 10_000_000_usize;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => inputs.push(value(&mut args)),
+            "--memory" => memory = value(&mut args),
+            "--fuel" => fuel = value(&mut args),
+            _ => usage(),
+        }
+    }
+
+    let program = load(&path);
+    let mut memory = vec![0; program.memory_size().max(memory)];
+    let image = program.image();
+    memory[..image.len()].copy_from_slice(&image);
+    let mut runner = Runner::new(&mut memory);
+    runner.extend_input(inputs);
+    let mut trace = Trace::new(runner);
+
+    let mut outputs = Vec::new();
+    let end = loop {
+        if fuel == 0 {
+            break Ok("ran out of fuel");
+        }
+        fuel -= 1;
+        match trace.step() {
+            Ok(Step::Continue) => {}
+            Ok(Step::Output(value)) => outputs.push(value),
+            Ok(Step::Blocked) => break Ok("blocked on input"),
+            Ok(Step::Halted) => break Ok("halted"),
+            Err(fault) => break Err(fault),
+        }
+    };
+    match end {
+        Ok(end) => println!("{} (ip={})", end, trace.runner().ip()),
+        // faults already say where they happened
+        Err(fault) => println!("{}", fault),
+    }
+    println!("outputs: {:?}", outputs);
+    println!();
+
+    let changes = trace.changes();
+    println!("{} cells changed:", changes.len());
+    for change in changes {
+        println!("{}", change);
+    }
+    println!();
+
+    let mut dump = String::new();
+    trace.dump(&mut dump).unwrap();
+    print!("{}", dump);
+}
@@ -0,0 +1,412 @@
+//! Running straight-line code a basic block at a time.
+//!
+//! A block is a run of instructions starting at some address that does no input or output and
+//! ends at the first jump. Blocks are decoded once and cached by the address they start at, so
+//! running one doesn't need to work out opcodes or parameter modes again. A compare followed by a
+//! jump is fused into a single superinstruction.
+//!
+//! Intcode programs often rewrite the parameters of their own instructions, to index into arrays
+//! for example, so blocks always read parameters from memory. Writing over the first word of an
+//! instruction throws away the blocks it's in, so other self-modifying code still works. Input,
+//! output, halting, custom instructions and anything that would fault are left to
+//! [`Runner::step`], so a runner gives exactly the same outputs and stops in exactly the same
+//! places with or without the cache.
+
+use crate::decode::{Instruction, Mode, Opcode};
+use crate::validate::Validation;
+use crate::{Runner, Step};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::task::Poll;
+
+/// The most instructions a block holds.
+const MAX_LEN: usize = 64;
+/// The most words a block can be decoded from.
+const MAX_WORDS: usize = MAX_LEN * 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Add,
+    Multiply,
+    LessThan,
+    Equals,
+    AdjustBase,
+    Jump {
+        when: bool,
+    },
+    /// A `lt` (or an `eq`) followed by a jump, at `jump`, whose parameters have `modes`.
+    CompareJump {
+        equals: bool,
+        jump: usize,
+        when: bool,
+        modes: [Mode; 2],
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Op {
+    /// The address of the instruction.
+    addr: usize,
+    kind: Kind,
+    modes: [Mode; 3],
+}
+
+#[derive(Debug, Clone)]
+struct Block {
+    ops: Vec<Op>,
+    /// The address of the first word after the block.
+    end: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Slot {
+    Unknown,
+    /// There's no block here: the instruction has to be stepped.
+    Empty,
+    Block(Block),
+}
+
+/// The blocks decoded from a runner's memory.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Cache {
+    slots: Vec<Slot>,
+    /// Whether each word is the first word of an instruction in a block. This is not unset when
+    /// blocks are thrown away, so it may be true for words that no longer are.
+    code: Vec<bool>,
+}
+
+/// How running a block ended.
+struct Exit {
+    ip: usize,
+    /// The address of an instruction word that was written to, stopping the block early.
+    dirty: Option<usize>,
+    /// Whether the block stopped on an instruction that has to be stepped instead.
+    bail: bool,
+}
+
+impl Cache {
+    /// Throws away any block with an instruction starting at `addr`.
+    pub(crate) fn invalidate(&mut self, addr: usize) {
+        if !self.code.get(addr).copied().unwrap_or(false) {
+            return;
+        }
+        for start in addr.saturating_sub(MAX_WORDS - 1)..=addr {
+            if let Slot::Block(block) = &self.slots[start] {
+                if addr < block.end {
+                    self.slots[start] = Slot::Unknown;
+                }
+            }
+        }
+    }
+
+    /// Throws away every block, keeping the memory used for them.
+    pub(crate) fn clear(&mut self) {
+        self.slots.fill(Slot::Unknown);
+        self.code.fill(false);
+    }
+
+    fn compile(&mut self, memory: &[i64], ip: usize, validation: Validation) {
+        let decode = match validation {
+            Validation::Lenient => Instruction::decode,
+            Validation::Strict => Instruction::decode_strict,
+        };
+
+        let mut ops: Vec<Op> = Vec::new();
+        let mut addr = ip;
+        while ops.len() < MAX_LEN {
+            let Ok(instruction) = decode(memory, addr) else {
+                break;
+            };
+            let mut modes = [Mode::Position; 3];
+            for (mode, param) in modes.iter_mut().zip(instruction.params()) {
+                *mode = param.mode;
+            }
+            let kind = match instruction.opcode {
+                Opcode::Add => Kind::Add,
+                Opcode::Multiply => Kind::Multiply,
+                Opcode::LessThan => Kind::LessThan,
+                Opcode::Equals => Kind::Equals,
+                Opcode::AdjustBase => Kind::AdjustBase,
+                Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                    let when = instruction.opcode == Opcode::JumpIfTrue;
+                    match ops.last_mut() {
+                        Some(op) if matches!(op.kind, Kind::LessThan | Kind::Equals) => {
+                            op.kind = Kind::CompareJump {
+                                equals: op.kind == Kind::Equals,
+                                jump: addr,
+                                when,
+                                modes: [modes[0], modes[1]],
+                            };
+                        }
+                        _ => ops.push(Op {
+                            addr,
+                            kind: Kind::Jump { when },
+                            modes,
+                        }),
+                    }
+                    self.code[addr] = true;
+                    addr = instruction.next();
+                    break;
+                }
+                Opcode::Input | Opcode::Output | Opcode::Halt => break,
+            };
+            ops.push(Op { addr, kind, modes });
+            self.code[addr] = true;
+            addr = instruction.next();
+        }
+
+        self.slots[ip] = if ops.is_empty() {
+            Slot::Empty
+        } else {
+            Slot::Block(Block { ops, end: addr })
+        };
+    }
+}
+
+fn address(memory: &[i64], value: i64) -> Option<usize> {
+    usize::try_from(value)
+        .ok()
+        .filter(|addr| *addr < memory.len())
+}
+
+/// The address the parameter at `at` refers to, or `None` if it's immediate or outside of memory.
+fn target(memory: &[i64], base: i64, mode: Mode, at: usize) -> Option<usize> {
+    let value = memory[at];
+    match mode {
+        Mode::Position => address(memory, value),
+        Mode::Immediate => None,
+        Mode::Relative => address(memory, base.checked_add(value)?),
+    }
+}
+
+fn value(memory: &[i64], base: i64, mode: Mode, at: usize) -> Option<i64> {
+    match mode {
+        Mode::Immediate => Some(memory[at]),
+        _ => target(memory, base, mode, at).map(|addr| memory[addr]),
+    }
+}
+
+/// Runs the jump at `addr`, going to `end` if it isn't taken. Both parameters are read either
+/// way, as [`Runner`] does.
+fn jump(memory: &[i64], base: i64, addr: usize, modes: [Mode; 2], when: bool, end: usize) -> Exit {
+    let (Some(condition), Some(target)) = (
+        value(memory, base, modes[0], addr + 1),
+        value(memory, base, modes[1], addr + 2),
+    ) else {
+        return Exit::bail(addr);
+    };
+    if (condition != 0) != when {
+        return Exit::next(end);
+    }
+    address(memory, target).map_or(Exit::bail(addr), Exit::next)
+}
+
+impl Exit {
+    fn next(ip: usize) -> Exit {
+        Exit {
+            ip,
+            dirty: None,
+            bail: false,
+        }
+    }
+
+    fn bail(ip: usize) -> Exit {
+        Exit {
+            ip,
+            dirty: None,
+            bail: true,
+        }
+    }
+
+    fn dirty(ip: usize, addr: usize) -> Exit {
+        Exit {
+            ip,
+            dirty: Some(addr),
+            bail: false,
+        }
+    }
+}
+
+impl Block {
+    /// Runs the block. An instruction that would fault stops the block without doing anything,
+    /// so the runner can step it instead.
+    fn run(&self, memory: &mut [i64], code: &[bool], base: &mut i64) -> Exit {
+        for (i, op) in self.ops.iter().enumerate() {
+            let Op { addr, kind, modes } = *op;
+            let a = || value(memory, *base, modes[0], addr + 1);
+            let b = || value(memory, *base, modes[1], addr + 2);
+            let result = match kind {
+                Kind::Add => a().zip(b()).and_then(|(a, b)| a.checked_add(b)),
+                Kind::Multiply => a().zip(b()).and_then(|(a, b)| a.checked_mul(b)),
+                Kind::LessThan | Kind::CompareJump { equals: false, .. } => {
+                    a().zip(b()).map(|(a, b)| i64::from(a < b))
+                }
+                Kind::Equals | Kind::CompareJump { equals: true, .. } => {
+                    a().zip(b()).map(|(a, b)| i64::from(a == b))
+                }
+                Kind::AdjustBase => {
+                    match a().and_then(|a| base.checked_add(a)) {
+                        Some(new) => *base = new,
+                        None => return Exit::bail(addr),
+                    }
+                    continue;
+                }
+                Kind::Jump { when } => {
+                    return jump(memory, *base, addr, [modes[0], modes[1]], when, self.end);
+                }
+            };
+            let Some((result, dest)) = result.zip(target(memory, *base, modes[2], addr + 3)) else {
+                return Exit::bail(addr);
+            };
+            memory[dest] = result;
+
+            let next = match kind {
+                Kind::CompareJump { jump, .. } => jump,
+                _ => self.ops.get(i + 1).map_or(self.end, |op| op.addr),
+            };
+            if code[dest] {
+                return Exit::dirty(next, dest);
+            }
+            if let Kind::CompareJump { when, modes, .. } = kind {
+                return jump(memory, *base, next, modes, when, self.end);
+            }
+        }
+        Exit::next(self.end)
+    }
+}
+
+impl Runner<'_> {
+    /// Like [`Iterator::next`], but running cached blocks between the instructions that can't be
+    /// cached. This is kept apart so that runners without a cache don't pay for checking it.
+    pub(crate) fn next_cached(&mut self) -> Option<Poll<i64>> {
+        loop {
+            self.run_blocks();
+            match self.step() {
+                Step::Continue => {}
+                Step::Output(value) => break Some(Poll::Ready(value)),
+                Step::Blocked => break Some(Poll::Pending),
+                Step::Halted => break None,
+            }
+        }
+    }
+
+    /// Runs cached blocks for as long as there are blocks to run.
+    pub(crate) fn run_blocks(&mut self) {
+        let Some(mut cache) = self.cache.take() else {
+            return;
+        };
+        if cache.slots.len() != self.program.len() {
+            cache.slots = vec![Slot::Unknown; self.program.len()];
+            cache.code = vec![false; self.program.len()];
+        }
+
+        while !self.halted && self.ip < self.program.len() {
+            if let Slot::Unknown = cache.slots[self.ip] {
+                cache.compile(self.program, self.ip, self.validation);
+            }
+            let exit = match &cache.slots[self.ip] {
+                Slot::Block(block) => block.run(self.program, &cache.code, &mut self.base),
+                _ => break,
+            };
+            self.ip = exit.ip;
+            if let Some(addr) = exit.dirty {
+                cache.invalidate(addr);
+            }
+            if exit.bail {
+                break;
+            }
+        }
+        self.cache = Some(cache);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_blocks() {
+    use crate::differential::{generate, Rng};
+    use crate::{Fault, PollExt};
+    use core::task::Poll;
+
+    // runs until the next output, input request, halt or fault, if it gets there within `fuel`
+    // instructions or blocks
+    fn event(runner: &mut Runner<'_>, fuel: &mut usize) -> Option<Result<Step, Fault>> {
+        while *fuel > 0 {
+            *fuel -= 1;
+            runner.run_blocks();
+            match runner.try_step() {
+                Ok(Step::Continue) => {}
+                result => return Some(result),
+            }
+        }
+        None
+    }
+
+    // count down from the input, then rewrite the loop so that it multiplies by -2 instead,
+    // which ends it; this changes both the parameters and the opcode of an instruction in the
+    // middle of the loop's block
+    let program = [
+        3, 30, // in [30]
+        1007, 30, 1, 31, // 2: lt [30], 1, [31]
+        1005, 31, 29, // jt [31], 29
+        4, 30, // 9: out [30]
+        1001, 30, -1, 30, // 11: add [30], -1, [30]
+        1101, -2, 0, 13, // add -2, 0, [13]
+        1101, 1002, 0, 11, // add 1002, 0, [11]
+        1106, 0, 2, // jf 0, 2
+        0, 0, 0,  // 26
+        99, // 29: hlt
+        0, 0,
+    ];
+    for caching in &[false, true] {
+        let mut memory = program;
+        let mut runner = Runner::new(&mut memory);
+        runner.set_caching(*caching);
+        runner.input(5);
+        assert_eq!(
+            runner.by_ref().map(PollExt::unwrap).collect::<Vec<_>>(),
+            [5, 4]
+        );
+        assert_eq!(memory[30..], [-8, 1]);
+    }
+
+    // blocking on input leaves the runner on the input instruction
+    let mut memory = [1101, 1, 2, 9, 3, 9, 4, 9, 99, 0];
+    let mut runner = Runner::new(&mut memory);
+    runner.set_caching(true);
+    assert_eq!(runner.next(), Some(Poll::Pending));
+    assert_eq!(runner.ip(), 4);
+    runner.input(5);
+    assert_eq!(runner.next(), Some(Poll::Ready(5)));
+
+    // random programs do the same thing either way
+    let mut rng = Rng::new(38);
+    for _ in 0..2000 {
+        let program = generate(&mut rng, 64);
+        let (mut plain, mut cached) = (program.clone(), program);
+        let mut a = Runner::new(&mut plain);
+        let mut b = Runner::new(&mut cached);
+        b.set_caching(true);
+        let mut fuel = 10_000;
+        // the plain runner might loop forever, so the cached one only runs as far as it did
+        let finished = loop {
+            let Some(result) = event(&mut a, &mut fuel) else {
+                break false;
+            };
+            assert_eq!(event(&mut b, &mut 10_000), Some(result));
+            assert_eq!((a.ip(), a.base()), (b.ip(), b.base()));
+            match result {
+                Ok(Step::Output(_)) => {}
+                Ok(Step::Blocked) => {
+                    a.input(7);
+                    b.input(7);
+                }
+                _ => break true,
+            }
+        };
+        drop((a, b));
+        if finished {
+            assert_eq!(plain, cached);
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{Runner, Step};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use core::task::Poll;
+
+const DEFAULT_INTERVAL: usize = 1024;
+const DEFAULT_CHECKPOINTS: usize = 64;
+
+/// Everything needed to undo a single step.
+#[derive(Debug, Clone, Copy)]
+struct Undo {
+    ip: usize,
+    base: i64,
+    halted: bool,
+    write: Option<(usize, i64)>,
+    consumed: bool,
+}
+
+#[derive(Debug)]
+struct Checkpoint {
+    time: usize,
+    memory: Vec<i64>,
+    ip: usize,
+    base: i64,
+    halted: bool,
+    cursor: usize,
+}
+
+/// A [`Runner`] that can step backwards as well as forwards.
+///
+/// Each step records an undo entry (the ip, relative base and any memory cell it overwrote). Every
+/// `interval` steps the full machine state is saved as a checkpoint and the undo log is cleared;
+/// stepping back past a checkpoint restores the one before it and replays forward. Only the last
+/// `checkpoints` checkpoints are kept, so memory use is bounded and history older than that is
+/// forgotten.
+///
+/// Inputs are kept on a tape rather than handed to the runner directly, so they can be put back
+/// when stepping backwards over an input instruction and fed again when replaying.
+pub struct Debugger<'a> {
+    runner: Runner<'a>,
+    time: usize,
+    undo: Vec<Undo>,
+    checkpoints: VecDeque<Checkpoint>,
+    tape: VecDeque<i64>,
+    tape_start: usize,
+    cursor: usize,
+    interval: usize,
+    max_checkpoints: usize,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(runner: Runner<'a>) -> Debugger<'a> {
+        Debugger::with_history(runner, DEFAULT_INTERVAL, DEFAULT_CHECKPOINTS)
+    }
+
+    /// Creates a debugger that checkpoints every `interval` steps and keeps at most `checkpoints`
+    /// of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` or `checkpoints` is zero.
+    pub fn with_history(
+        mut runner: Runner<'a>,
+        interval: usize,
+        checkpoints: usize,
+    ) -> Debugger<'a> {
+        assert!(interval > 0, "checkpoint interval must be nonzero");
+        assert!(checkpoints > 0, "must keep at least one checkpoint");
+        let mut tape = VecDeque::new();
+        tape.extend(runner.input.drain(..));
+        let mut debugger = Debugger {
+            runner,
+            time: 0,
+            undo: Vec::new(),
+            checkpoints: VecDeque::new(),
+            tape,
+            tape_start: 0,
+            cursor: 0,
+            interval,
+            max_checkpoints: checkpoints,
+        };
+        debugger.checkpoint();
+        debugger
+    }
+
+    pub fn runner(&self) -> &Runner<'a> {
+        &self.runner
+    }
+
+    /// The number of steps executed so far.
+    pub fn time(&self) -> usize {
+        self.time
+    }
+
+    /// The earliest time that can still be stepped back to.
+    pub fn earliest(&self) -> usize {
+        self.checkpoints.front().map_or(0, |c| c.time)
+    }
+
+    pub fn input(&mut self, input: i64) {
+        self.tape.push_back(input);
+    }
+
+    /// Executes a single instruction, recording how to undo it.
+    pub fn step(&mut self) -> Step {
+        let step = self.forward();
+        if self.undo.len() == self.interval {
+            self.checkpoint();
+        }
+        step
+    }
+
+    /// Undoes the most recent step. Returns `false` if there is no history left.
+    pub fn step_back(&mut self) -> bool {
+        self.back().is_some()
+    }
+
+    /// Steps backwards until just before the instruction that last wrote `addr`, returning the
+    /// address of that instruction.
+    ///
+    /// If no write to `addr` is found in the remaining history, this returns `None` and leaves
+    /// the debugger at the earliest point it can reach.
+    pub fn step_back_to_write(&mut self, addr: usize) -> Option<usize> {
+        loop {
+            let undo = self.back()?;
+            if let Some((write, _)) = undo.write {
+                if write == addr {
+                    break Some(undo.ip);
+                }
+            }
+        }
+    }
+
+    fn forward(&mut self) -> Step {
+        if self.runner.halted {
+            return Step::Halted;
+        }
+
+        let consumed = self
+            .runner
+            .program
+            .get(self.runner.ip)
+            .is_some_and(|opcode| opcode % 100 == 3);
+        if consumed {
+            match self.tape.get(self.cursor - self.tape_start) {
+                Some(&input) => self.runner.input(input),
+                None => return Step::Blocked,
+            }
+            self.cursor += 1;
+        }
+
+        let mut undo = Undo {
+            ip: self.runner.ip,
+            base: self.runner.base,
+            halted: self.runner.halted,
+            write: None,
+            consumed,
+        };
+        let step = self.runner.step();
+        undo.write = self.runner.last_write;
+        self.undo.push(undo);
+        self.time += 1;
+        step
+    }
+
+    fn back(&mut self) -> Option<Undo> {
+        if self.undo.is_empty() {
+            // We're sitting on the latest checkpoint; rebuild the undo log for the segment
+            // before it by replaying from the previous checkpoint.
+            if self.checkpoints.len() < 2 {
+                return None;
+            }
+            let target = self.time;
+            self.checkpoints.pop_back();
+            self.restore();
+            while self.time < target {
+                self.forward();
+            }
+        }
+
+        let undo = self.undo.pop()?;
+        self.runner.ip = undo.ip;
+        self.runner.base = undo.base;
+        self.runner.halted = undo.halted;
+        if let Some((addr, old)) = undo.write {
+            self.runner.program[addr] = old;
+        }
+        if undo.consumed {
+            self.cursor -= 1;
+        }
+        self.time -= 1;
+        Some(undo)
+    }
+
+    fn checkpoint(&mut self) {
+        self.undo.clear();
+        self.checkpoints.push_back(Checkpoint {
+            time: self.time,
+            memory: self.runner.program.to_vec(),
+            ip: self.runner.ip,
+            base: self.runner.base,
+            halted: self.runner.halted,
+            cursor: self.cursor,
+        });
+        if self.checkpoints.len() > self.max_checkpoints {
+            self.checkpoints.pop_front();
+            // Inputs consumed before the oldest checkpoint can never be replayed.
+            if let Some(oldest) = self.checkpoints.front() {
+                while self.tape_start < oldest.cursor {
+                    self.tape.pop_front();
+                    self.tape_start += 1;
+                }
+            }
+        }
+    }
+
+    fn restore(&mut self) {
+        let checkpoint = self.checkpoints.back().expect("no checkpoint to restore");
+        self.runner.program.copy_from_slice(&checkpoint.memory);
+        self.runner.ip = checkpoint.ip;
+        self.runner.base = checkpoint.base;
+        self.runner.halted = checkpoint.halted;
+        self.runner.input.clear();
+        self.cursor = checkpoint.cursor;
+        self.time = checkpoint.time;
+        self.undo.clear();
+    }
+}
+
+impl Iterator for Debugger<'_> {
+    type Item = Poll<i64>;
+
+    fn next(&mut self) -> Option<Poll<i64>> {
+        loop {
+            match self.step() {
+                Step::Continue => {}
+                Step::Output(value) => break Some(Poll::Ready(value)),
+                Step::Blocked => break Some(Poll::Pending),
+                Step::Halted => break None,
+            }
+        }
+    }
+}
+
+impl Debug for Debugger<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Debugger")
+            .field("runner", &self.runner)
+            .field("time", &self.time)
+            .field("earliest", &self.earliest())
+            .field(
+                "pending_input",
+                &(self.tape.len() + self.tape_start - self.cursor),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_step_back() {
+    use crate::PollExt;
+
+    const QUINE: [i64; 16] = [
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ];
+
+    // record every state of a plain forward run
+    let mut memory = [0; 102];
+    memory[..16].copy_from_slice(&QUINE);
+    let mut states = Vec::new();
+    {
+        let mut runner = Runner::new(&mut memory);
+        states.push((runner.ip(), runner.base(), runner.memory().to_vec()));
+        loop {
+            let step = runner.step();
+            states.push((runner.ip(), runner.base(), runner.memory().to_vec()));
+            if step == Step::Halted {
+                break;
+            }
+        }
+    }
+
+    let mut memory = [0; 102];
+    memory[..16].copy_from_slice(&QUINE);
+    let mut debugger = Debugger::with_history(Runner::new(&mut memory), 7, 1000);
+    assert_eq!(debugger.by_ref().map(PollExt::unwrap).count(), 16);
+    assert_eq!(debugger.time(), states.len() - 1);
+    for state in states.iter().rev().skip(1) {
+        assert!(debugger.step_back());
+        let runner = debugger.runner();
+        assert_eq!(
+            &(runner.ip(), runner.base(), runner.memory().to_vec()),
+            state
+        );
+    }
+    assert!(!debugger.step_back());
+    assert_eq!(
+        debugger.by_ref().map(PollExt::unwrap).collect::<Vec<_>>(),
+        QUINE
+    );
+
+    // only the last three checkpoints are kept
+    let mut memory = [0; 102];
+    memory[..16].copy_from_slice(&QUINE);
+    let mut debugger = Debugger::with_history(Runner::new(&mut memory), 7, 3);
+    debugger.by_ref().for_each(drop);
+    let earliest = debugger.earliest();
+    assert!(earliest > 0);
+    while debugger.step_back() {}
+    assert_eq!(debugger.time(), earliest);
+    assert_eq!(debugger.runner().ip(), states[earliest].0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_step_back_input() {
+    use crate::PollExt;
+
+    // read two numbers, output their sum
+    let mut program = [3, 11, 3, 12, 1, 11, 12, 13, 4, 13, 99, 0, 0, 0];
+    let mut debugger = Debugger::with_history(Runner::new(&mut program), 2, 8);
+    debugger.input(3);
+    assert_eq!(debugger.next(), Some(Poll::Pending));
+    debugger.input(4);
+    assert_eq!(debugger.next().unwrap().unwrap(), 7);
+
+    // step back to the write that produced the output, then replay it
+    assert_eq!(debugger.step_back_to_write(13), Some(4));
+    assert_eq!(debugger.runner().memory()[13], 0);
+    assert_eq!(debugger.next().unwrap().unwrap(), 7);
+
+    // inputs are put back on the tape when stepping back over them
+    while debugger.step_back() {}
+    assert_eq!(debugger.time(), 0);
+    assert_eq!(debugger.next().unwrap().unwrap(), 7);
+    assert_eq!(debugger.next(), None);
+}
@@ -0,0 +1,335 @@
+use crate::opcodes::Kind;
+use crate::validate;
+use crate::FaultKind;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustBase,
+    Halt,
+}
+
+impl Opcode {
+    /// Looks up the opcode in the low two digits of an instruction word.
+    pub fn from_word(word: i64) -> Option<Opcode> {
+        use Opcode::*;
+        Some(match word % 100 {
+            1 => Add,
+            2 => Multiply,
+            3 => Input,
+            4 => Output,
+            5 => JumpIfTrue,
+            6 => JumpIfFalse,
+            7 => LessThan,
+            8 => Equals,
+            9 => AdjustBase,
+            99 => Halt,
+            _ => return None,
+        })
+    }
+
+    pub fn code(self) -> i64 {
+        use Opcode::*;
+        match self {
+            Add => 1,
+            Multiply => 2,
+            Input => 3,
+            Output => 4,
+            JumpIfTrue => 5,
+            JumpIfFalse => 6,
+            LessThan => 7,
+            Equals => 8,
+            AdjustBase => 9,
+            Halt => 99,
+        }
+    }
+
+    /// The number of parameters the instruction takes.
+    pub fn arity(self) -> usize {
+        use Opcode::*;
+        match self {
+            Add | Multiply | LessThan | Equals => 3,
+            JumpIfTrue | JumpIfFalse => 2,
+            Input | Output | AdjustBase => 1,
+            Halt => 0,
+        }
+    }
+
+    /// Whether the last parameter is an address that gets written to.
+    pub fn writes(self) -> bool {
+        use Opcode::*;
+        matches!(self, Add | Multiply | Input | LessThan | Equals)
+    }
+
+    /// The kinds of the instruction's parameters.
+    pub fn params(self) -> &'static [Kind] {
+        use Kind::{Address, Value};
+        use Opcode::*;
+        match self {
+            Add | Multiply | LessThan | Equals => &[Value, Value, Address],
+            JumpIfTrue | JumpIfFalse => &[Value, Value],
+            Input => &[Address],
+            Output | AdjustBase => &[Value],
+            Halt => &[],
+        }
+    }
+
+    pub fn mnemonic(self) -> &'static str {
+        use Opcode::*;
+        match self {
+            Add => "add",
+            Multiply => "mul",
+            Input => "in",
+            Output => "out",
+            JumpIfTrue => "jt",
+            JumpIfFalse => "jf",
+            LessThan => "lt",
+            Equals => "eq",
+            AdjustBase => "arb",
+            Halt => "hlt",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Param {
+    pub mode: Mode,
+    pub value: i64,
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            Mode::Position => write!(f, "[{}]", self.value),
+            Mode::Immediate => write!(f, "{}", self.value),
+            Mode::Relative if self.value < 0 => write!(f, "[rb-{}]", self.value.unsigned_abs()),
+            Mode::Relative => write!(f, "[rb+{}]", self.value),
+        }
+    }
+}
+
+/// A decoded instruction.
+///
+/// Decoding is as lenient as [`Runner`](crate::Runner): mode digits beyond the instruction's
+/// parameters are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    pub addr: usize,
+    pub word: i64,
+    pub opcode: Opcode,
+    params: [Param; 3],
+}
+
+impl Instruction {
+    pub fn decode(memory: &[i64], addr: usize) -> Result<Instruction, FaultKind> {
+        let word = *memory.get(addr).ok_or(FaultKind::EndOfMemory)?;
+        let opcode = Opcode::from_word(word).ok_or(FaultKind::IllegalInstruction(word))?;
+        let mut params = [Param {
+            mode: Mode::Position,
+            value: 0,
+        }; 3];
+        let mut modes = word / 100;
+        for (i, param) in params.iter_mut().enumerate().take(opcode.arity()) {
+            param.mode = match modes % 10 {
+                0 => Mode::Position,
+                1 if !(opcode.writes() && i + 1 == opcode.arity()) => Mode::Immediate,
+                2 => Mode::Relative,
+                _ => return Err(FaultKind::IllegalMode(word)),
+            };
+            param.value = *memory.get(addr + 1 + i).ok_or(FaultKind::EndOfMemory)?;
+            modes /= 10;
+        }
+        Ok(Instruction {
+            addr,
+            word,
+            opcode,
+            params,
+        })
+    }
+
+    /// Decodes an instruction, rejecting words that [`Validation::Strict`] would.
+    ///
+    /// [`Validation::Strict`]: crate::validate::Validation::Strict
+    pub fn decode_strict(memory: &[i64], addr: usize) -> Result<Instruction, FaultKind> {
+        let instruction = Instruction::decode(memory, addr)?;
+        validate::check_modes(instruction.word, instruction.opcode.params())?;
+        Ok(instruction)
+    }
+
+    pub fn params(&self) -> &[Param] {
+        &self.params[..self.opcode.arity()]
+    }
+
+    /// The number of words the instruction occupies.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        1 + self.opcode.arity()
+    }
+
+    /// The address of the next instruction, if execution falls through.
+    pub fn next(&self) -> usize {
+        self.addr + self.len()
+    }
+
+    /// The value the instruction stores, if it's an addition or multiplication of immediates.
+    pub fn constant(&self) -> Option<i64> {
+        let params = self.params();
+        if params.len() < 2 || params[..2].iter().any(|p| p.mode != Mode::Immediate) {
+            return None;
+        }
+        match self.opcode {
+            Opcode::Add => params[0].value.checked_add(params[1].value),
+            Opcode::Multiply => params[0].value.checked_mul(params[1].value),
+            _ => None,
+        }
+    }
+
+    /// Where execution can go after this instruction, as far as can be told without running it:
+    /// whether it can fall through to the next instruction, and the target of a jump, if the
+    /// target is immediate and inside memory of size `len`.
+    pub fn successors(&self, len: usize) -> (bool, Option<usize>) {
+        let params = self.params();
+        match self.opcode {
+            Opcode::Halt => (false, None),
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                let jumps_if = self.opcode == Opcode::JumpIfTrue;
+                let target = Some(params[1])
+                    .filter(|target| target.mode == Mode::Immediate)
+                    .and_then(|target| usize::try_from(target.value).ok())
+                    .filter(|target| *target < len);
+                if params[0].mode == Mode::Immediate {
+                    if (params[0].value != 0) == jumps_if {
+                        (false, target)
+                    } else {
+                        (true, None)
+                    }
+                } else {
+                    (true, target)
+                }
+            }
+            _ => (true, None),
+        }
+    }
+}
+
+/// Decodes the instructions that execution can reach from address 0, in address order.
+///
+/// See [`preflight`](crate::validate::preflight) for how reachability is worked out, and its
+/// limits. Instructions that fail to decode are left out.
+pub fn disassemble(memory: &[i64]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    walk(memory, Instruction::decode, |_, result| {
+        if let Ok(instruction) = result {
+            instructions.push(*instruction);
+        }
+    });
+    instructions.sort_by_key(|instruction| instruction.addr);
+    instructions
+}
+
+/// Visits each address execution can reach from address 0 (as described for
+/// [`preflight`](crate::validate::preflight)), with the result of decoding it.
+pub(crate) fn walk(
+    memory: &[i64],
+    decode: fn(&[i64], usize) -> Result<Instruction, FaultKind>,
+    mut visit: impl FnMut(usize, &Result<Instruction, FaultKind>),
+) {
+    let mut seen = vec![false; memory.len() + 1];
+    let mut queue = vec![0];
+    while let Some(addr) = queue.pop() {
+        if seen[addr] {
+            continue;
+        }
+        seen[addr] = true;
+        let result = decode(memory, addr);
+        visit(addr, &result);
+        if let Ok(instruction) = result {
+            let (falls_through, target) = instruction.successors(memory.len());
+            if falls_through {
+                queue.push(instruction.next());
+            }
+            queue.extend(target);
+            queue.extend(return_address(memory, &instruction));
+        }
+    }
+}
+
+/// If `instruction` is the first half of a call, the address the call returns to.
+fn return_address(memory: &[i64], instruction: &Instruction) -> Option<usize> {
+    let stored = instruction.constant()?;
+    let jump = Instruction::decode(memory, instruction.next()).ok()?;
+    match jump.successors(memory.len()) {
+        (false, Some(_)) if usize::try_from(stored) == Ok(jump.next()) => Some(jump.next()),
+        _ => None,
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.opcode.mnemonic())?;
+        for (i, param) in self.params().iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { ", " }, param)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_decode() {
+    use alloc::string::ToString;
+
+    let memory = [1002, 4, 3, 4, 33, 109, -1, 21_101, 1, 2, 3, 11_104, 0, 99];
+    let add = Instruction::decode(&memory, 0).unwrap();
+    assert_eq!(add.opcode, Opcode::Multiply);
+    assert_eq!(add.len(), 4);
+    assert_eq!(add.to_string(), "mul [4], 3, [4]");
+    assert_eq!(
+        Instruction::decode(&memory, 4),
+        Err(FaultKind::IllegalInstruction(33))
+    );
+    assert_eq!(
+        Instruction::decode(&memory, 5).unwrap().to_string(),
+        "arb -1"
+    );
+    assert_eq!(
+        Instruction::decode(&memory, 7).unwrap().to_string(),
+        "add 1, 2, [rb+3]"
+    );
+    // stray mode digits are ignored
+    assert_eq!(
+        Instruction::decode(&memory, 11).unwrap().to_string(),
+        "out 0"
+    );
+    assert_eq!(Instruction::decode(&memory, 13).unwrap().len(), 1);
+    assert_eq!(
+        Instruction::decode(&[11_101, 1, 2, 3], 0),
+        Err(FaultKind::IllegalMode(11_101))
+    );
+    assert_eq!(
+        Instruction::decode_strict(&memory, 11),
+        Err(FaultKind::IllegalMode(11_104))
+    );
+    assert_eq!(
+        Instruction::decode(&[1, 1, 2], 0),
+        Err(FaultKind::EndOfMemory)
+    );
+}
@@ -0,0 +1,515 @@
+//! Differential testing of intcode execution engines.
+//!
+//! [`lockstep`] runs the same program and inputs through several engines, stopping them at every
+//! output, input request and halt to compare what they have done so far. [`generate`] makes random
+//! programs to feed it.
+
+use crate::decode::{Instruction, Mode, Opcode, Param};
+use crate::{Fault, FaultKind, Runner, Step};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Something that can execute intcode.
+pub trait Engine {
+    /// Runs until the next output, input request or halt, executing at most `fuel` instructions.
+    ///
+    /// `fuel` is decremented for every instruction executed. Halting, blocking on input and
+    /// faulting do not use any. If `fuel` runs out first, this returns `Step::Continue`.
+    fn run(&mut self, fuel: &mut usize) -> Result<Step, Fault>;
+
+    fn input(&mut self, input: i64);
+
+    fn ip(&self) -> usize;
+
+    fn base(&self) -> i64;
+
+    fn memory(&self) -> &[i64];
+}
+
+impl Engine for Runner<'_> {
+    fn run(&mut self, fuel: &mut usize) -> Result<Step, Fault> {
+        while *fuel > 0 {
+            match self.try_step()? {
+                Step::Continue => *fuel -= 1,
+                Step::Output(value) => {
+                    *fuel -= 1;
+                    return Ok(Step::Output(value));
+                }
+                step => return Ok(step),
+            }
+        }
+        Ok(Step::Continue)
+    }
+
+    fn input(&mut self, input: i64) {
+        Runner::input(self, input);
+    }
+
+    fn ip(&self) -> usize {
+        Runner::ip(self)
+    }
+
+    fn base(&self) -> i64 {
+        Runner::base(self)
+    }
+
+    fn memory(&self) -> &[i64] {
+        Runner::memory(self)
+    }
+}
+
+/// A deliberately simple engine that decodes each instruction in full before executing it, to
+/// check faster engines against.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    memory: Vec<i64>,
+    ip: usize,
+    base: i64,
+    halted: bool,
+    input: Option<i64>,
+}
+
+impl Reference {
+    pub fn new(memory: Vec<i64>) -> Reference {
+        Reference {
+            memory,
+            ip: 0,
+            base: 0,
+            halted: false,
+            input: None,
+        }
+    }
+
+    fn address(&self, value: i64) -> Result<usize, FaultKind> {
+        usize::try_from(value)
+            .ok()
+            .filter(|addr| *addr < self.memory.len())
+            .ok_or(FaultKind::IllegalAddress(value))
+    }
+
+    fn value(&self, param: Param) -> Result<i64, FaultKind> {
+        Ok(match param.mode {
+            Mode::Immediate => param.value,
+            Mode::Position | Mode::Relative => self.memory[self.target(param)?],
+        })
+    }
+
+    fn target(&self, param: Param) -> Result<usize, FaultKind> {
+        match param.mode {
+            Mode::Position => self.address(param.value),
+            Mode::Relative => self.address(
+                self.base
+                    .checked_add(param.value)
+                    .ok_or(FaultKind::Overflow)?,
+            ),
+            Mode::Immediate => unreachable!("decoded an immediate-mode write"),
+        }
+    }
+
+    fn execute(&mut self) -> Result<Step, FaultKind> {
+        let instruction = Instruction::decode(&self.memory, self.ip)?;
+        let p = instruction.params();
+        let mut next = instruction.next();
+        let mut step = Step::Continue;
+        match instruction.opcode {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => {
+                let (a, b) = (self.value(p[0])?, self.value(p[1])?);
+                let result = match instruction.opcode {
+                    Opcode::Add => a.checked_add(b).ok_or(FaultKind::Overflow)?,
+                    Opcode::Multiply => a.checked_mul(b).ok_or(FaultKind::Overflow)?,
+                    Opcode::LessThan => i64::from(a < b),
+                    _ => i64::from(a == b),
+                };
+                let target = self.target(p[2])?;
+                self.memory[target] = result;
+            }
+            Opcode::Input => match self.input {
+                Some(input) => {
+                    let target = self.target(p[0])?;
+                    self.memory[target] = input;
+                    self.input = None;
+                }
+                None => return Ok(Step::Blocked),
+            },
+            Opcode::Output => step = Step::Output(self.value(p[0])?),
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                let (condition, target) = (self.value(p[0])? != 0, self.value(p[1])?);
+                if condition == (instruction.opcode == Opcode::JumpIfTrue) {
+                    next = self.address(target)?;
+                }
+            }
+            Opcode::AdjustBase => {
+                self.base = self
+                    .base
+                    .checked_add(self.value(p[0])?)
+                    .ok_or(FaultKind::Overflow)?;
+            }
+            Opcode::Halt => {
+                self.halted = true;
+                step = Step::Halted;
+            }
+        }
+        self.ip = next;
+        Ok(step)
+    }
+}
+
+impl Engine for Reference {
+    fn run(&mut self, fuel: &mut usize) -> Result<Step, Fault> {
+        if self.halted {
+            return Ok(Step::Halted);
+        }
+        while *fuel > 0 {
+            let step = self.execute().map_err(|kind| Fault { ip: self.ip, kind })?;
+            match step {
+                Step::Continue => *fuel -= 1,
+                Step::Output(_) => {
+                    *fuel -= 1;
+                    return Ok(step);
+                }
+                Step::Blocked | Step::Halted => return Ok(step),
+            }
+        }
+        Ok(Step::Continue)
+    }
+
+    fn input(&mut self, input: i64) {
+        self.input = Some(input);
+    }
+
+    fn ip(&self) -> usize {
+        self.ip
+    }
+
+    fn base(&self) -> i64 {
+        self.base
+    }
+
+    fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+}
+
+/// The first point at which an engine disagreed with the first engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// How many times the engines had been stopped and compared before this.
+    pub event: usize,
+    /// The index of the engine that disagreed.
+    pub engine: usize,
+    pub mismatch: Mismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The engines stopped for different reasons. Faults only need to agree on the address of the
+    /// faulting instruction, since an instruction can have more than one thing wrong with it.
+    Result {
+        expected: Result<Step, Fault>,
+        actual: Result<Step, Fault>,
+    },
+    /// The engines executed a different number of instructions.
+    Fuel {
+        expected: usize,
+        actual: usize,
+    },
+    Ip {
+        expected: usize,
+        actual: usize,
+    },
+    Base {
+        expected: i64,
+        actual: i64,
+    },
+    /// A memory cell differs, or only exists in one of the engines.
+    Memory {
+        addr: usize,
+        expected: Option<i64>,
+        actual: Option<i64>,
+    },
+}
+
+/// What the engines agreed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub outputs: Vec<i64>,
+    /// Why the engines stopped: `Step::Continue` if they ran out of fuel, `Step::Blocked` if they
+    /// wanted more input than they were given.
+    pub end: Result<Step, Fault>,
+    /// The number of instructions executed.
+    pub steps: usize,
+}
+
+/// Runs `engines` in lockstep, feeding each of them `inputs` in order and executing at most `fuel`
+/// instructions. The first engine is the one the others are compared against.
+///
+/// # Panics
+///
+/// Panics if `engines` is empty.
+pub fn lockstep(
+    engines: &mut [&mut dyn Engine],
+    inputs: &[i64],
+    fuel: usize,
+) -> Result<Summary, Divergence> {
+    assert!(!engines.is_empty(), "no engines to run");
+    let mut fuels = alloc::vec![fuel; engines.len()];
+    let mut inputs = inputs.iter();
+    let mut outputs = Vec::new();
+    let mut event = 0;
+    let end = loop {
+        let results = engines
+            .iter_mut()
+            .zip(fuels.iter_mut())
+            .map(|(engine, fuel)| engine.run(fuel))
+            .collect::<Vec<_>>();
+        for i in 1..engines.len() {
+            let divergence = |mismatch| Divergence {
+                event,
+                engine: i,
+                mismatch,
+            };
+            compare(&results[0], &results[i]).map_err(divergence)?;
+            if fuels[0] != fuels[i] {
+                return Err(divergence(Mismatch::Fuel {
+                    expected: fuels[0],
+                    actual: fuels[i],
+                }));
+            }
+            compare_state(&*engines[0], &*engines[i]).map_err(divergence)?;
+        }
+
+        match results[0] {
+            Ok(Step::Output(value)) => outputs.push(value),
+            Ok(Step::Blocked) => match inputs.next() {
+                Some(input) => {
+                    for engine in engines.iter_mut() {
+                        engine.input(*input);
+                    }
+                }
+                None => break results[0],
+            },
+            result => break result,
+        }
+        event += 1;
+    };
+    Ok(Summary {
+        outputs,
+        end,
+        steps: fuel - fuels[0],
+    })
+}
+
+fn compare(expected: &Result<Step, Fault>, actual: &Result<Step, Fault>) -> Result<(), Mismatch> {
+    let same = match (expected, actual) {
+        (Err(expected), Err(actual)) => expected.ip == actual.ip,
+        _ => expected == actual,
+    };
+    if same {
+        Ok(())
+    } else {
+        Err(Mismatch::Result {
+            expected: *expected,
+            actual: *actual,
+        })
+    }
+}
+
+fn compare_state(expected: &dyn Engine, actual: &dyn Engine) -> Result<(), Mismatch> {
+    if expected.ip() != actual.ip() {
+        return Err(Mismatch::Ip {
+            expected: expected.ip(),
+            actual: actual.ip(),
+        });
+    }
+    if expected.base() != actual.base() {
+        return Err(Mismatch::Base {
+            expected: expected.base(),
+            actual: actual.base(),
+        });
+    }
+    let (expected, actual) = (expected.memory(), actual.memory());
+    for addr in 0..expected.len().max(actual.len()) {
+        let (expected, actual) = (expected.get(addr).copied(), actual.get(addr).copied());
+        if expected != actual {
+            return Err(Mismatch::Memory {
+                addr,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// A small xorshift random number generator, so generated programs are reproducible from a seed.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // xorshift gets stuck at zero
+        Rng(seed ^ 0x9e37_79b9_7f4a_7c15)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `0..n`.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns a number in `lo..=hi`.
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        let span = hi.wrapping_sub(lo).cast_unsigned().wrapping_add(1);
+        lo.wrapping_add((self.next_u64() % span).cast_signed())
+    }
+
+    pub fn chance(&mut self, one_in: usize) -> bool {
+        self.below(one_in) == 0
+    }
+}
+
+/// Generates a random program of `len` words.
+///
+/// Programs are mostly made of valid instructions with valid parameter modes, whose position
+/// parameters point inside the program, with the occasional garbage word thrown in so faults get
+/// exercised too.
+pub fn generate(rng: &mut Rng, len: usize) -> Vec<i64> {
+    use Opcode::*;
+    const OPCODES: [Opcode; 16] = [
+        Add,
+        Add,
+        Add,
+        Multiply,
+        Multiply,
+        LessThan,
+        LessThan,
+        Equals,
+        Input,
+        Output,
+        Output,
+        JumpIfTrue,
+        JumpIfTrue,
+        JumpIfFalse,
+        AdjustBase,
+        Halt,
+    ];
+
+    let addr = |rng: &mut Rng| rng.range(0, i64::try_from(len).unwrap_or(i64::MAX) - 1);
+    let mut program = Vec::with_capacity(len);
+    while program.len() < len {
+        if rng.chance(32) {
+            program.push(rng.range(-200, 22_299));
+            continue;
+        }
+
+        let opcode = OPCODES[rng.below(OPCODES.len())];
+        let mut word = opcode.code();
+        let mut params = Vec::new();
+        let mut place = 100;
+        for i in 0..opcode.arity() {
+            let write = opcode.writes() && i + 1 == opcode.arity();
+            let mode = match rng.below(10) {
+                0..=3 => 0,
+                4..=7 if !write => 1,
+                _ => 2,
+            };
+            params.push(match mode {
+                0 => addr(rng),
+                1 if rng.chance(2) => addr(rng),
+                _ => rng.range(-8, 8),
+            });
+            word += mode * place;
+            place *= 10;
+        }
+        program.push(word);
+        program.extend(params);
+    }
+    program.truncate(len);
+    program
+}
+
+#[cfg(test)]
+#[test]
+fn test_lockstep() {
+    let mut rng = Rng::new(2019);
+    let mut ends = [0; 4];
+    for _ in 0..500 {
+        let program = generate(&mut rng, 48);
+        let inputs = (0..rng.below(3))
+            .map(|_| rng.range(-3, 50))
+            .collect::<Vec<_>>();
+        let mut memory = program.clone();
+        let mut runner = Runner::new(&mut memory);
+        let mut reference = Reference::new(program.clone());
+        let summary = lockstep(&mut [&mut reference, &mut runner], &inputs, 500).unwrap();
+        ends[match summary.end {
+            Ok(Step::Halted) => 0,
+            Ok(Step::Blocked) => 1,
+            Ok(_) => 2,
+            Err(_) => 3,
+        }] += 1;
+    }
+    // make sure the generator exercises every way of stopping
+    assert!(ends.iter().all(|count| *count > 0), "{:?}", ends);
+}
+
+#[cfg(test)]
+#[test]
+fn test_divergence() {
+    struct OffByOne(Reference);
+
+    impl Engine for OffByOne {
+        fn run(&mut self, fuel: &mut usize) -> Result<Step, Fault> {
+            match self.0.run(fuel) {
+                Ok(Step::Output(value)) if value > 2 => Ok(Step::Output(value + 1)),
+                result => result,
+            }
+        }
+
+        fn input(&mut self, input: i64) {
+            self.0.input(input);
+        }
+
+        fn ip(&self) -> usize {
+            self.0.ip()
+        }
+
+        fn base(&self) -> i64 {
+            self.0.base()
+        }
+
+        fn memory(&self) -> &[i64] {
+            self.0.memory()
+        }
+    }
+
+    // count down from the input
+    let program = alloc::vec![3, 12, 4, 12, 1001, 12, -1, 12, 1005, 12, 2, 99, 0];
+    let mut memory = program.clone();
+    let mut runner = Runner::new(&mut memory);
+    let mut broken = OffByOne(Reference::new(program.clone()));
+    let summary = lockstep(&mut [&mut runner], &[5], 1000).unwrap();
+    assert_eq!(summary.outputs, [5, 4, 3, 2, 1]);
+    assert_eq!(summary.end, Ok(Step::Halted));
+
+    let mut memory = program.clone();
+    let mut runner = Runner::new(&mut memory);
+    assert_eq!(
+        lockstep(&mut [&mut runner, &mut broken], &[5], 1000),
+        Err(Divergence {
+            event: 1,
+            engine: 1,
+            mismatch: Mismatch::Result {
+                expected: Ok(Step::Output(5)),
+                actual: Ok(Step::Output(6)),
+            },
+        })
+    );
+}
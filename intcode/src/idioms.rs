@@ -0,0 +1,525 @@
+//! Recognising common idioms in intcode programs.
+//!
+//! Intcode has no call instruction, no indirect addressing and no multiply loop, so programs build
+//! them out of the same few instruction sequences. [`recognise`] finds those sequences in a
+//! disassembly, [`list`] labels them in a listing, and an [`Accelerator`] runs recognised
+//! multiplication loops in a single step.
+
+use crate::decode::{self, Instruction, Mode, Opcode, Param};
+use crate::{Fault, Runner, Step};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt::{self, Debug, Display};
+use core::ops::Range;
+use core::task::Poll;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Idiom {
+    /// Stores the return address, then jumps to a function.
+    Call { target: usize },
+    /// Pops a stack frame with `arb`, then jumps to the return address stored in it.
+    Return,
+    /// Makes room for a function's stack frame with `arb`.
+    Prologue { size: i64 },
+    /// Adds `x` to `acc` once for each time it counts `counter` down to zero; that is,
+    /// `acc += x * counter`.
+    Multiply {
+        acc: usize,
+        x: usize,
+        counter: usize,
+    },
+    /// Moves words from one place to another, advancing both by rewriting its own operands.
+    Copy,
+    /// Writes the same value over a run of memory, advancing by rewriting its own operand.
+    Fill,
+    /// Outputs words from successive addresses, advancing by rewriting its own operand or by
+    /// moving the relative base.
+    Print,
+    /// Reads input until a newline.
+    ReadLine,
+}
+
+impl Display for Idiom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Idiom::Call { target } => write!(f, "call {}", target),
+            Idiom::Return => write!(f, "return"),
+            Idiom::Prologue { size } => write!(f, "prologue, {} word frame", size),
+            Idiom::Multiply { acc, x, counter } => {
+                write!(f, "multiply: [{}] += [{}] * [{}]", acc, x, counter)
+            }
+            Idiom::Copy => write!(f, "copy loop"),
+            Idiom::Fill => write!(f, "fill loop"),
+            Idiom::Print => write!(f, "print loop"),
+            Idiom::ReadLine => write!(f, "read line"),
+        }
+    }
+}
+
+/// An idiom found at a range of addresses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub range: Range<usize>,
+    pub idiom: Idiom,
+}
+
+/// Finds idioms in a disassembly, as returned by [`disassemble`](decode::disassemble). Matches
+/// are returned in order of where they start; loops can contain other matches.
+pub fn recognise(instructions: &[Instruction]) -> Vec<Match> {
+    let mut matches = Vec::new();
+    for (i, instruction) in instructions.iter().enumerate() {
+        let next = instructions
+            .get(i + 1)
+            .filter(|next| next.addr == instruction.next());
+        if let Some(next) = next {
+            matches.extend(call(instruction, next).or_else(|| ret(instruction, next)));
+        }
+        matches.extend(body(instructions, i).and_then(classify));
+    }
+
+    let targets = matches
+        .iter()
+        .filter_map(|m| match m.idiom {
+            Idiom::Call { target } => Some(target),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for instruction in instructions {
+        if instruction.opcode == Opcode::AdjustBase
+            && targets.contains(&instruction.addr)
+            && instruction.params()[0].mode == Mode::Immediate
+        {
+            matches.push(Match {
+                range: instruction.addr..instruction.next(),
+                idiom: Idiom::Prologue {
+                    size: instruction.params()[0].value,
+                },
+            });
+        }
+    }
+
+    matches.sort_by_key(|m| m.range.start);
+    matches
+}
+
+/// Writes a listing of the reachable code in `memory`, with recognised idioms labelled.
+pub fn list(memory: &[i64], f: &mut dyn fmt::Write) -> fmt::Result {
+    let instructions = decode::disassemble(memory);
+    let matches = recognise(&instructions);
+    let mut matches = matches.iter().peekable();
+    let mut last = None;
+    for instruction in &instructions {
+        if last.is_some_and(|last| last != instruction.addr) {
+            writeln!(f)?;
+        }
+        while let Some(m) = matches.next_if(|m| m.range.start <= instruction.addr) {
+            writeln!(f, "; {}", m.idiom)?;
+        }
+        writeln!(f, "{:>6}: {}", instruction.addr, instruction)?;
+        last = Some(instruction.next());
+    }
+    Ok(())
+}
+
+fn jump_target(instruction: &Instruction) -> Option<usize> {
+    match instruction.successors(usize::MAX) {
+        (false, Some(target)) => Some(target),
+        _ => None,
+    }
+}
+
+fn call(store: &Instruction, jump: &Instruction) -> Option<Match> {
+    let target = jump_target(jump)?;
+    if usize::try_from(store.constant()?) != Ok(jump.next()) {
+        return None;
+    }
+    Some(Match {
+        range: store.addr..jump.next(),
+        idiom: Idiom::Call { target },
+    })
+}
+
+fn ret(arb: &Instruction, jump: &Instruction) -> Option<Match> {
+    let pops = arb.opcode == Opcode::AdjustBase
+        && arb.params()[0].mode == Mode::Immediate
+        && arb.params()[0].value < 0;
+    let unconditional = matches!(jump.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse)
+        && !jump.successors(usize::MAX).0;
+    if pops && unconditional && jump.params()[1].mode == Mode::Relative {
+        Some(Match {
+            range: arb.addr..jump.next(),
+            idiom: Idiom::Return,
+        })
+    } else {
+        None
+    }
+}
+
+/// If the instruction at `end` jumps back to an earlier instruction, the body of the loop that
+/// makes, as long as it's straight-line code.
+fn body(instructions: &[Instruction], end: usize) -> Option<&[Instruction]> {
+    let jump = &instructions[end];
+    if !matches!(jump.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse) {
+        return None;
+    }
+    let target = jump.successors(usize::MAX).1?;
+    if target > jump.addr {
+        return None;
+    }
+    let start = instructions[..end]
+        .iter()
+        .rposition(|instruction| instruction.addr == target)?;
+    let body = &instructions[start..=end];
+    if body.windows(2).all(|w| w[0].next() == w[1].addr) {
+        Some(body)
+    } else {
+        None
+    }
+}
+
+/// The address a position-mode parameter refers to.
+fn position(param: Param) -> Option<usize> {
+    match param.mode {
+        Mode::Position => usize::try_from(param.value).ok(),
+        _ => None,
+    }
+}
+
+/// If `instruction` adds `step` to a cell in place, that cell's address.
+fn increments(instruction: &Instruction, step: i64) -> Option<usize> {
+    if instruction.opcode != Opcode::Add {
+        return None;
+    }
+    let p = instruction.params();
+    let target = position(p[2])?;
+    let immediate = Param {
+        mode: Mode::Immediate,
+        value: step,
+    };
+    if (p[0] == immediate && position(p[1]) == Some(target))
+        || (p[1] == immediate && position(p[0]) == Some(target))
+    {
+        Some(target)
+    } else {
+        None
+    }
+}
+
+/// If `instruction` copies a value (by adding zero or multiplying by one), the source and
+/// destination parameters, along with the address of the source's operand.
+fn moves(instruction: &Instruction) -> Option<(Param, Param, usize)> {
+    let identity = match instruction.opcode {
+        Opcode::Add => 0,
+        Opcode::Multiply => 1,
+        _ => return None,
+    };
+    let p = instruction.params();
+    let identity = Param {
+        mode: Mode::Immediate,
+        value: identity,
+    };
+    if p[1] == identity {
+        Some((p[0], p[2], instruction.addr + 1))
+    } else if p[0] == identity {
+        Some((p[1], p[2], instruction.addr + 2))
+    } else {
+        None
+    }
+}
+
+fn classify(body: &[Instruction]) -> Option<Match> {
+    let range = body[0].addr..body[body.len() - 1].next();
+    let idiom = multiply(body, &range)
+        .or_else(|| pointer_loop(body))
+        .or_else(|| read_line(body))?;
+    Some(Match { range, idiom })
+}
+
+fn multiply(body: &[Instruction], range: &Range<usize>) -> Option<Idiom> {
+    let (add, decrement, jump) = match body {
+        [a, b, jump] if increments(b, -1).is_some() => (a, b, jump),
+        [a, b, jump] => (b, a, jump),
+        _ => return None,
+    };
+    let counter = increments(decrement, -1)?;
+    if add.opcode != Opcode::Add
+        || jump.opcode != Opcode::JumpIfTrue
+        || position(jump.params()[0]) != Some(counter)
+    {
+        return None;
+    }
+    let p = add.params();
+    let acc = position(p[2])?;
+    let x = if position(p[0]) == Some(acc) {
+        position(p[1])?
+    } else if position(p[1]) == Some(acc) {
+        position(p[0])?
+    } else {
+        return None;
+    };
+    let cells = [acc, x, counter];
+    if acc == x || x == counter || acc == counter || cells.iter().any(|c| range.contains(c)) {
+        return None;
+    }
+    Some(Idiom::Multiply { acc, x, counter })
+}
+
+fn pointer_loop(body: &[Instruction]) -> Option<Idiom> {
+    let incremented = body
+        .iter()
+        .filter_map(|instruction| increments(instruction, 1))
+        .collect::<Vec<_>>();
+    let moves_base = body.iter().any(|instruction| {
+        instruction.opcode == Opcode::AdjustBase
+            && instruction.params()[0]
+                == Param {
+                    mode: Mode::Immediate,
+                    value: 1,
+                }
+    });
+    for instruction in body {
+        if instruction.opcode == Opcode::Output {
+            let advances = match instruction.params()[0].mode {
+                Mode::Position => incremented.contains(&(instruction.addr + 1)),
+                Mode::Immediate => false,
+                Mode::Relative => moves_base,
+            };
+            if advances {
+                return Some(Idiom::Print);
+            }
+        } else if let Some((source, dest, source_cell)) = moves(instruction) {
+            let dest_cell = instruction.addr + 3;
+            let source_advances =
+                source.mode == Mode::Position && incremented.contains(&source_cell);
+            let dest_advances = dest.mode == Mode::Position && incremented.contains(&dest_cell);
+            match (source_advances, dest_advances) {
+                (true, true) => return Some(Idiom::Copy),
+                (false, true) => return Some(Idiom::Fill),
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+fn read_line(body: &[Instruction]) -> Option<Idiom> {
+    let newline = Param {
+        mode: Mode::Immediate,
+        value: 10,
+    };
+    body.iter()
+        .filter(|instruction| instruction.opcode == Opcode::Input)
+        .filter_map(|instruction| position(instruction.params()[0]))
+        .any(|cell| {
+            body.iter().any(|instruction| {
+                let p = instruction.params();
+                instruction.opcode == Opcode::Equals
+                    && ((position(p[0]) == Some(cell) && p[1] == newline)
+                        || (p[0] == newline && position(p[1]) == Some(cell)))
+            })
+        })
+        .then_some(Idiom::ReadLine)
+}
+
+#[derive(Debug)]
+struct Multiply {
+    end: usize,
+    code: Vec<i64>,
+    acc: usize,
+    x: usize,
+    counter: usize,
+}
+
+/// A [`Runner`] that executes recognised multiplication loops in one step.
+///
+/// The loops are found when the accelerator is created. Before a loop is skipped, its code is
+/// checked against what was recognised, in case the program has rewritten it, and the loop is
+/// only skipped when the result is the same as running it: the counter has to be positive and
+/// the multiplication can't overflow. Otherwise the loop runs as normal.
+pub struct Accelerator<'a> {
+    runner: Runner<'a>,
+    loops: BTreeMap<usize, Multiply>,
+    skipped: usize,
+}
+
+impl<'a> Accelerator<'a> {
+    pub fn new(runner: Runner<'a>) -> Accelerator<'a> {
+        let instructions = decode::disassemble(runner.memory());
+        let loops = recognise(&instructions)
+            .into_iter()
+            .filter_map(|m| match m.idiom {
+                Idiom::Multiply { acc, x, counter } => Some((
+                    m.range.start,
+                    Multiply {
+                        end: m.range.end,
+                        code: runner.memory()[m.range].to_vec(),
+                        acc,
+                        x,
+                        counter,
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+        Accelerator {
+            runner,
+            loops,
+            skipped: 0,
+        }
+    }
+
+    pub fn runner(&self) -> &Runner<'a> {
+        &self.runner
+    }
+
+    pub fn input(&mut self, input: i64) {
+        self.runner.input(input);
+    }
+
+    /// The number of loops that have been skipped.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Executes a single instruction, or a whole multiplication loop.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instruction faults.
+    pub fn step(&mut self) -> Step {
+        self.try_step().unwrap_or_else(|fault| panic!("{}", fault))
+    }
+
+    /// Executes a single instruction, or a whole multiplication loop, returning an error if it
+    /// faults.
+    pub fn try_step(&mut self) -> Result<Step, Fault> {
+        if !self.runner.halted && self.multiply() {
+            self.skipped += 1;
+            return Ok(Step::Continue);
+        }
+        self.runner.try_step()
+    }
+
+    fn multiply(&mut self) -> bool {
+        let runner = &mut self.runner;
+        let Some(m) = self.loops.get(&runner.ip) else {
+            return false;
+        };
+        if runner.program[runner.ip..m.end] != m.code[..] {
+            return false;
+        }
+        let counter = runner.program[m.counter];
+        if counter <= 0 {
+            return false;
+        }
+        let acc = runner.program[m.x]
+            .checked_mul(counter)
+            .and_then(|product| runner.program[m.acc].checked_add(product));
+        match acc {
+            Some(acc) => {
+                runner.program[m.acc] = acc;
+                runner.program[m.counter] = 0;
+                runner.ip = m.end;
+                runner.last_write = None;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Iterator for Accelerator<'_> {
+    type Item = Poll<i64>;
+
+    fn next(&mut self) -> Option<Poll<i64>> {
+        loop {
+            match self.step() {
+                Step::Continue => {}
+                Step::Output(value) => break Some(Poll::Ready(value)),
+                Step::Blocked => break Some(Poll::Pending),
+                Step::Halted => break None,
+            }
+        }
+    }
+}
+
+impl Debug for Accelerator<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Accelerator")
+            .field("runner", &self.runner)
+            .field("loops", &self.loops.keys())
+            .field("skipped", &self.skipped)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_idioms() {
+    use crate::PollExt;
+    use alloc::string::String;
+
+    // multiply the input by 7, then call a function that prints 42
+    let mut program = [0; 53];
+    program[..38].copy_from_slice(&[
+        3, 52, // in [52]
+        1101, 0, 7, 51, // add 0, 7, [51]
+        1, 50, 51, 50, // 6: add [50], [51], [50]
+        1001, 52, -1, 52, // add [52], -1, [52]
+        1005, 52, 6, // jt [52], 6
+        4, 50, // out [50]
+        109, 40, // arb 40
+        21101, 28, 0, 0, // add 28, 0, [rb+0]
+        1105, 1, 29, // jt 1, 29
+        99, // 28: hlt
+        109, 1, // 29: arb 1
+        104, 42, // out 42
+        109, -1, // arb -1
+        2105, 1, 0, // jt 1, [rb+0]
+    ]);
+
+    let mut listing = String::new();
+    list(&program, &mut listing).unwrap();
+    assert_eq!(
+        listing.lines().collect::<Vec<_>>(),
+        [
+            "     0: in [52]",
+            "     2: add 0, 7, [51]",
+            "; multiply: [50] += [51] * [52]",
+            "     6: add [50], [51], [50]",
+            "    10: add [52], -1, [52]",
+            "    14: jt [52], 6",
+            "    17: out [50]",
+            "    19: arb 40",
+            "; call 29",
+            "    21: add 28, 0, [rb+0]",
+            "    25: jt 1, 29",
+            "    28: hlt",
+            "; prologue, 1 word frame",
+            "    29: arb 1",
+            "    31: out 42",
+            "; return",
+            "    33: arb -1",
+            "    35: jt 1, [rb+0]",
+        ]
+    );
+
+    for input in &[1, 6, 1000] {
+        let mut memory = program;
+        let mut runner = Runner::new(&mut memory);
+        runner.input(*input);
+        let expected = runner.map(PollExt::unwrap).collect::<Vec<_>>();
+        let mut memory = program;
+        let mut accelerator = Accelerator::new(Runner::new(&mut memory));
+        accelerator.input(*input);
+        assert_eq!(
+            accelerator
+                .by_ref()
+                .map(PollExt::unwrap)
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(accelerator.skipped(), 1);
+    }
+}
@@ -0,0 +1,259 @@
+//! Looking at what a program did to its memory.
+
+use crate::decode::{Instruction, Mode};
+use crate::{Fault, Runner, Step};
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt::{self, Display};
+use core::ops::Range;
+
+/// What a memory cell was used for, as far as execution showed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Region {
+    /// Never executed or used as an operand.
+    Unknown,
+    /// Read or written by an instruction.
+    Data,
+    /// Executed as part of an instruction. This wins over `Data` for self-modifying code.
+    Code,
+}
+
+impl Region {
+    fn name(self) -> &'static str {
+        match self {
+            Region::Unknown => "unknown",
+            Region::Data => "data",
+            Region::Code => "code",
+        }
+    }
+}
+
+/// A memory cell with a different value in two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    pub addr: usize,
+    pub old: i64,
+    pub new: i64,
+    /// The address of the last instruction that wrote the cell, if known.
+    pub writer: Option<usize>,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>6}: {} -> {}", self.addr, self.old, self.new)?;
+        if let Some(writer) = self.writer {
+            write!(f, " (written at {})", writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two memory snapshots. Cells past the end of the shorter one count as zero.
+pub fn diff(before: &[i64], after: &[i64]) -> Vec<Change> {
+    let len = before.len().max(after.len());
+    let get = |memory: &[i64], addr| memory.get(addr).copied().unwrap_or(0);
+    (0..len)
+        .filter(|addr| get(before, *addr) != get(after, *addr))
+        .map(|addr| Change {
+            addr,
+            old: get(before, addr),
+            new: get(after, addr),
+            writer: None,
+        })
+        .collect()
+}
+
+/// A [`Runner`] that keeps track of which instruction wrote each cell, and which cells are code
+/// and which are data.
+#[derive(Debug)]
+pub struct Trace<'a> {
+    runner: Runner<'a>,
+    before: Vec<i64>,
+    writers: Vec<Option<usize>>,
+    regions: Vec<Region>,
+}
+
+impl<'a> Trace<'a> {
+    /// Starts tracing, taking a snapshot of memory to compare against.
+    pub fn new(runner: Runner<'a>) -> Trace<'a> {
+        let len = runner.memory().len();
+        Trace {
+            before: runner.memory().to_vec(),
+            runner,
+            writers: vec![None; len],
+            regions: vec![Region::Unknown; len],
+        }
+    }
+
+    pub fn runner(&self) -> &Runner<'a> {
+        &self.runner
+    }
+
+    pub fn input(&mut self, input: i64) {
+        self.runner.input(input);
+    }
+
+    /// Executes a single instruction; see [`Runner::try_step`].
+    pub fn step(&mut self) -> Result<Step, Fault> {
+        let ip = self.runner.ip();
+        let base = self.runner.base();
+        let instruction = Instruction::decode(self.runner.memory(), ip).ok();
+        let step = self.runner.try_step()?;
+        if step == Step::Blocked {
+            return Ok(step);
+        }
+
+        // Custom instructions can't be decoded, so only their opcode is known to be code.
+        let len = instruction.map_or(1, |instruction| instruction.len());
+        for addr in ip..(ip + len).min(self.regions.len()) {
+            self.regions[addr] = Region::Code;
+        }
+        for param in instruction.iter().flat_map(Instruction::params) {
+            let addr = match param.mode {
+                Mode::Position => param.value,
+                Mode::Immediate => continue,
+                Mode::Relative => base.saturating_add(param.value),
+            };
+            if let Some(region) = usize::try_from(addr)
+                .ok()
+                .and_then(|addr| self.regions.get_mut(addr))
+            {
+                *region = (*region).max(Region::Data);
+            }
+        }
+        if let Some((addr, _)) = self.runner.last_write() {
+            self.writers[addr] = Some(ip);
+        }
+        Ok(step)
+    }
+
+    /// Runs until the program halts or blocks on input, returning its outputs.
+    pub fn run(&mut self) -> Result<Vec<i64>, Fault> {
+        let mut outputs = Vec::new();
+        loop {
+            match self.step()? {
+                Step::Continue => {}
+                Step::Output(value) => outputs.push(value),
+                Step::Blocked | Step::Halted => break Ok(outputs),
+            }
+        }
+    }
+
+    /// The cells that have changed since tracing started.
+    pub fn changes(&self) -> Vec<Change> {
+        let mut changes = diff(&self.before, self.runner.memory());
+        for change in &mut changes {
+            change.writer = self.writers[change.addr];
+        }
+        changes
+    }
+
+    /// What each cell has been used for so far.
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// Splits memory into runs of cells used for the same thing.
+    pub fn spans(&self) -> Vec<(Range<usize>, Region)> {
+        let mut spans: Vec<(Range<usize>, Region)> = Vec::new();
+        for (addr, region) in self.regions.iter().enumerate() {
+            match spans.last_mut() {
+                Some((range, last)) if last == region => range.end = addr + 1,
+                _ => spans.push((addr..addr + 1, *region)),
+            }
+        }
+        spans
+    }
+
+    /// Writes a hexdump-like view of memory, split into code and data regions. Changed cells are
+    /// marked with a `*`, and long runs of zeros that were never used are left out.
+    pub fn dump(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        const WIDTH: usize = 8;
+
+        let memory = self.runner.memory();
+        for (range, region) in self.spans() {
+            if region == Region::Unknown && memory[range.clone()].iter().all(|word| *word == 0) {
+                writeln!(
+                    f,
+                    "{:>6}..{}: {} zeros",
+                    range.start,
+                    range.end,
+                    range.len()
+                )?;
+                continue;
+            }
+            writeln!(f, "{} {}..{}", region.name(), range.start, range.end)?;
+            for row in (range.start..range.end).step_by(WIDTH) {
+                write!(f, "{:>6}:", row)?;
+                let end = (row + WIDTH).min(range.end);
+                for (before, after) in self.before[row..end].iter().zip(&memory[row..end]) {
+                    let marker = if before == after { "" } else { "*" };
+                    write!(f, " {:>12}", format!("{}{}", marker, after))?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_trace() {
+    use alloc::string::{String, ToString};
+
+    // read a number, store its double, output it
+    let mut program = [
+        3, 9, 102, 2, 9, 10, 4, 10, 99, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    let mut trace = Trace::new(Runner::new(&mut program));
+    assert_eq!(trace.run(), Ok(Vec::new()));
+    trace.input(21);
+    assert_eq!(trace.run(), Ok(vec![42]));
+
+    let changes = trace.changes();
+    assert_eq!(
+        changes,
+        [
+            Change {
+                addr: 9,
+                old: 0,
+                new: 21,
+                writer: Some(0),
+            },
+            Change {
+                addr: 10,
+                old: 0,
+                new: 42,
+                writer: Some(2),
+            },
+        ]
+    );
+    assert_eq!(changes[1].to_string(), "    10: 0 -> 42 (written at 2)");
+    assert_eq!(
+        trace.spans(),
+        [
+            (0..9, Region::Code),
+            (9..11, Region::Data),
+            (11..20, Region::Unknown),
+        ]
+    );
+
+    let mut dump = String::new();
+    trace.dump(&mut dump).unwrap();
+    assert_eq!(
+        dump.lines().collect::<Vec<_>>(),
+        [
+            "code 0..9",
+            "     0:            3            9          102            2            9           10            4           10",
+            "     8:           99",
+            "data 9..11",
+            "     9:          *21          *42",
+            "    11..20: 9 zeros",
+        ]
+    );
+
+    assert_eq!(diff(&[1, 2], &[1, 3, 4])[1].addr, 2);
+}
@@ -1,7 +1,39 @@
 #![no_std]
 #![warn(clippy::pedantic)]
-#![allow(clippy::use_self)]
+#![allow(
+    clippy::enum_glob_use,
+    clippy::missing_errors_doc,
+    clippy::must_use_candidate,
+    clippy::uninlined_format_args,
+    clippy::use_self
+)]
 
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod blocks;
+mod debugger;
+pub mod decode;
+pub mod differential;
+pub mod idioms;
+pub mod inspect;
+mod machine;
+pub mod opcodes;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod program;
+pub mod session;
+pub mod validate;
+pub mod wasm;
+
+pub use crate::debugger::Debugger;
+pub use crate::machine::Machine;
+
+use crate::decode::Opcode;
+use crate::opcodes::{Context, Custom, Kind, Opcodes};
+use crate::validate::Validation;
+use alloc::collections::VecDeque;
 use core::convert::TryFrom;
 use core::fmt::{self, Debug};
 use core::task::Poll;
@@ -19,13 +51,82 @@ impl<T> PollExt<T> for Poll<T> {
     }
 }
 
+/// The result of executing a single instruction with [`Runner::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// An instruction ran without producing output.
+    Continue,
+    /// An output instruction ran.
+    Output(i64),
+    /// The program is waiting on input; nothing ran.
+    Blocked,
+    /// The program has halted.
+    Halted,
+}
+
+/// An instruction that could not be executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fault {
+    /// The address of the faulting instruction.
+    pub ip: usize,
+    pub kind: FaultKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultKind {
+    /// The opcode (the instruction word is included) is not known.
+    IllegalInstruction(i64),
+    /// A parameter mode (the instruction word is included) is not known, or is immediate mode
+    /// for a parameter that is written to.
+    IllegalMode(i64),
+    /// A parameter refers to an address outside of memory.
+    IllegalAddress(i64),
+    /// The instruction runs past the end of memory.
+    EndOfMemory,
+    /// An arithmetic result or the relative base does not fit in an `i64`.
+    Overflow,
+    /// A custom instruction failed.
+    Custom(&'static str),
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            FaultKind::IllegalInstruction(word) => write!(f, "illegal instruction {}", word),
+            FaultKind::IllegalMode(word) => write!(f, "illegal parameter mode {}", word),
+            FaultKind::IllegalAddress(addr) => write!(f, "illegal address {}", addr),
+            FaultKind::EndOfMemory => write!(f, "ran off the end of memory"),
+            FaultKind::Overflow => write!(f, "arithmetic overflow"),
+            FaultKind::Custom(msg) => write!(f, "{}", msg),
+        }?;
+        write!(f, " (ip={})", self.ip)
+    }
+}
+
 pub struct Runner<'a> {
     program: &'a mut [i64],
     ip: usize,
     base: i64,
     halted: bool,
-    input: Option<i64>,
+    input: VecDeque<i64>,
     register: [i64; 2],
+    last_write: Option<(usize, i64)>,
+    opcodes: Option<&'a Opcodes>,
+    validation: Validation,
+    cache: Option<blocks::Cache>,
+}
+
+/// The state of a [`Runner`] apart from its memory, so that it can be put away while the memory
+/// is used for something else.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct State {
+    ip: usize,
+    base: i64,
+    halted: bool,
+    input: VecDeque<i64>,
+    last_write: Option<(usize, i64)>,
+    validation: Validation,
+    cache: Option<blocks::Cache>,
 }
 
 impl<'a> Runner<'a> {
@@ -35,13 +136,127 @@ impl<'a> Runner<'a> {
             ip: 0,
             base: 0,
             halted: false,
-            input: None,
+            input: VecDeque::new(),
             register: [0; 2],
+            last_write: None,
+            opcodes: None,
+            validation: Validation::Lenient,
+            cache: None,
+        }
+    }
+
+    /// Creates a runner that also understands the custom instructions in `opcodes`.
+    pub fn with_opcodes(program: &'a mut [i64], opcodes: &'a Opcodes) -> Runner<'a> {
+        Runner {
+            opcodes: Some(opcodes),
+            ..Runner::new(program)
+        }
+    }
+
+    pub(crate) fn resume(
+        program: &'a mut [i64],
+        state: State,
+        opcodes: Option<&'a Opcodes>,
+    ) -> Runner<'a> {
+        Runner {
+            opcodes,
+            ip: state.ip,
+            base: state.base,
+            halted: state.halted,
+            input: state.input,
+            last_write: state.last_write,
+            validation: state.validation,
+            cache: state.cache,
+            ..Runner::new(program)
+        }
+    }
+
+    pub(crate) fn into_state(self) -> State {
+        State {
+            ip: self.ip,
+            base: self.base,
+            halted: self.halted,
+            input: self.input,
+            last_write: self.last_write,
+            validation: self.validation,
+            cache: self.cache,
+        }
+    }
+
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn base(&self) -> i64 {
+        self.base
+    }
+
+    pub fn validation(&self) -> Validation {
+        self.validation
+    }
+
+    /// Sets how strictly instruction words are checked before they're executed.
+    pub fn set_validation(&mut self, validation: Validation) {
+        self.validation = validation;
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Turns the basic block cache on or off.
+    ///
+    /// With the cache on, iterating over the runner decodes straight-line code without input or
+    /// output once, and runs it a block at a time after that, which is faster for programs that
+    /// loop a lot. Writes to code are noticed, so it makes no difference to what the program
+    /// does. [`step`](Runner::step) and [`try_step`](Runner::try_step) still execute one
+    /// instruction at a time.
+    pub fn set_caching(&mut self, caching: bool) {
+        if caching != self.cache.is_some() {
+            self.cache = if caching {
+                Some(blocks::Cache::default())
+            } else {
+                None
+            };
         }
     }
 
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        self.program
+    }
+
+    /// The address written by the most recent [`step`](Runner::step), along with the value that
+    /// was there before.
+    pub fn last_write(&self) -> Option<(usize, i64)> {
+        self.last_write
+    }
+
+    /// Adds a value to the end of the input queue.
+    ///
+    /// Input instructions take values from the front of the queue. If the queue is empty, the
+    /// runner blocks instead: [`step`](Runner::step) returns [`Step::Blocked`] and the runner
+    /// stays on the input instruction, so stepping again after more input is queued carries on
+    /// where it left off.
     pub fn input(&mut self, input: i64) {
-        self.input = Some(input);
+        self.input.push_back(input);
+    }
+
+    /// Adds values to the end of the input queue, in order.
+    pub fn extend_input<I: IntoIterator<Item = i64>>(&mut self, inputs: I) {
+        self.input.extend(inputs);
+    }
+
+    /// The values queued for input instructions that haven't executed yet, from first to last.
+    pub fn pending_input(&self) -> &VecDeque<i64> {
+        &self.input
+    }
+
+    /// Discards any queued input.
+    pub fn clear_input(&mut self) {
+        self.input.clear();
     }
 
     pub fn full_input<I, T>(self, input: I) -> FullRunner<'a, I::IntoIter>
@@ -55,28 +270,8 @@ impl<'a> Runner<'a> {
         }
     }
 
-    fn panic(&self, msg: &str, ip_offset: usize) -> ! {
-        panic!(
-            "{} (ip={} mem={})",
-            msg,
-            self.ip - ip_offset,
-            self.program[self.ip - ip_offset]
-        )
-    }
-
-    fn usize(&self, value: i64, msg: &str, ip_offset: usize) -> usize {
-        if let Ok(value) = usize::try_from(value) {
-            value
-        } else {
-            panic!(
-                "{} {} (ip={} mem={} base={})",
-                msg,
-                value,
-                self.ip - ip_offset,
-                self.program[self.ip - ip_offset],
-                self.base
-            );
-        }
+    fn panic(&self, msg: &str) -> ! {
+        panic!("{} (ip={} mem={})", msg, self.ip, self.program[self.ip])
     }
 
     pub fn run(&mut self) {
@@ -87,24 +282,35 @@ impl<'a> Runner<'a> {
                 None => break true,
             }
         } {
-            self.panic("program blocked on input", 0)
+            self.panic("program blocked on input")
         }
     }
 
-    fn pop(&mut self) -> i64 {
-        let x = self.program[self.ip];
+    fn pop(&mut self) -> Result<i64, FaultKind> {
+        let x = *self.program.get(self.ip).ok_or(FaultKind::EndOfMemory)?;
         self.ip += 1;
-        x
+        Ok(x)
     }
 
-    fn read(&mut self, opcode: i64, n: usize) {
+    fn address(&self, value: i64) -> Result<usize, FaultKind> {
+        usize::try_from(value)
+            .ok()
+            .filter(|addr| *addr < self.program.len())
+            .ok_or(FaultKind::IllegalAddress(value))
+    }
+
+    fn relative(&self, value: i64) -> Result<usize, FaultKind> {
+        self.address(self.base.checked_add(value).ok_or(FaultKind::Overflow)?)
+    }
+
+    fn read(&mut self, opcode: i64, n: usize) -> Result<(), FaultKind> {
         let mut params = opcode / 100;
         for i in 0..n {
-            let value = self.pop();
+            let value = self.pop()?;
             self.register[i] = match params % 10 {
                 0 => {
                     // position
-                    self.program[self.usize(value, "illegal position value", 1)]
+                    self.program[self.address(value)?]
                 }
                 1 => {
                     // immediate
@@ -112,102 +318,219 @@ impl<'a> Runner<'a> {
                 }
                 2 => {
                     // relative
-                    self.program[self.usize(self.base + value, "illegal relative value", 1)]
+                    self.program[self.relative(value)?]
                 }
-                _ => self.panic("illegal value parameter mode", 1),
+                _ => return Err(FaultKind::IllegalMode(opcode)),
             };
             params /= 10;
         }
+        Ok(())
     }
 
-    fn addr(&mut self, mode: i64) -> &mut i64 {
-        let x = match mode % 10 {
-            0 => self.pop(),
-            2 => self.pop() + self.base,
-            _ => self.panic("illegal address parameter mode", 0),
+    fn write(&mut self, opcode: i64, mode: i64, value: i64) -> Result<(), FaultKind> {
+        let addr = match mode % 10 {
+            0 => {
+                let value = self.pop()?;
+                self.address(value)?
+            }
+            2 => {
+                let value = self.pop()?;
+                self.relative(value)?
+            }
+            _ => return Err(FaultKind::IllegalMode(opcode)),
         };
-        &mut self.program[self.usize(x, "illegal address", 1)]
+        let old = core::mem::replace(&mut self.program[addr], value);
+        self.last_write = Some((addr, old));
+        Ok(())
     }
-}
 
-impl Iterator for Runner<'_> {
-    type Item = Poll<i64>;
+    /// Executes a single instruction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instruction faults; see [`try_step`](Runner::try_step).
+    pub fn step(&mut self) -> Step {
+        self.try_step().unwrap_or_else(|fault| panic!("{}", fault))
+    }
 
-    fn next(&mut self) -> Option<Poll<i64>> {
+    /// Executes a single instruction, returning an error if it faults.
+    ///
+    /// A faulting instruction has no effect: the runner is left pointing at it, with memory as it
+    /// was before.
+    pub fn try_step(&mut self) -> Result<Step, Fault> {
+        let step = self.try_step_uncached()?;
+        if let (Some(cache), Some((addr, _))) = (&mut self.cache, self.last_write) {
+            cache.invalidate(addr);
+        }
+        Ok(step)
+    }
+
+    /// Like [`try_step`](Runner::try_step), but without telling the block cache what was
+    /// written, for when there's no cache to tell.
+    fn try_step_uncached(&mut self) -> Result<Step, Fault> {
         if self.halted {
-            return None;
+            return Ok(Step::Halted);
         }
 
-        loop {
-            let opcode = self.pop();
-            match opcode % 100 {
-                1 => {
-                    // add
-                    self.read(opcode, 2);
-                    *self.addr(opcode / 10000) = self.register[0] + self.register[1];
-                }
-                2 => {
-                    // multiply
-                    self.read(opcode, 2);
-                    *self.addr(opcode / 10000) = self.register[0] * self.register[1];
-                }
-                3 => {
-                    // write input
-                    if let Some(input) = core::mem::replace(&mut self.input, None) {
-                        *self.addr(opcode / 100) = input;
-                    } else {
-                        self.ip -= 1;
-                        break Some(Poll::Pending);
-                    }
-                }
-                4 => {
-                    // read output
-                    self.read(opcode, 1);
-                    break Some(Poll::Ready(self.register[0]));
-                }
-                5 => {
-                    // jump-if-true
-                    self.read(opcode, 2);
-                    if self.register[0] != 0 {
-                        self.ip = self.usize(self.register[1], "illegal address", 1);
+        self.last_write = None;
+        let ip = self.ip;
+        self.execute().map_err(|kind| {
+            self.ip = ip;
+            Fault { ip, kind }
+        })
+    }
+
+    fn execute(&mut self) -> Result<Step, FaultKind> {
+        let opcode = self.pop()?;
+        if self.validation == Validation::Strict {
+            let params = match Opcode::from_word(opcode) {
+                Some(op) => op.params(),
+                None => match self.opcodes.and_then(|opcodes| opcodes.get(opcode % 100)) {
+                    Some(custom) => custom.params,
+                    None => return Err(FaultKind::IllegalInstruction(opcode)),
+                },
+            };
+            validate::check_modes(opcode, params)?;
+        }
+        match opcode % 100 {
+            1 => {
+                // add
+                self.read(opcode, 2)?;
+                let value = self.register[0].checked_add(self.register[1]);
+                self.write(opcode, opcode / 10000, value.ok_or(FaultKind::Overflow)?)?;
+            }
+            2 => {
+                // multiply
+                self.read(opcode, 2)?;
+                let value = self.register[0].checked_mul(self.register[1]);
+                self.write(opcode, opcode / 10000, value.ok_or(FaultKind::Overflow)?)?;
+            }
+            3 => {
+                // write input
+                if let Some(&input) = self.input.front() {
+                    self.write(opcode, opcode / 100, input)?;
+                    self.input.pop_front();
+                } else {
+                    // the instruction still has to be valid to block on it
+                    if !matches!((opcode / 100) % 10, 0 | 2) {
+                        return Err(FaultKind::IllegalMode(opcode));
                     }
-                }
-                6 => {
-                    // jump-if-false
-                    self.read(opcode, 2);
-                    if self.register[0] == 0 {
-                        self.ip = self.usize(self.register[1], "illegal address", 1);
+                    if self.ip == self.program.len() {
+                        return Err(FaultKind::EndOfMemory);
                     }
+                    self.ip -= 1;
+                    return Ok(Step::Blocked);
                 }
-                7 => {
-                    // less than
-                    self.read(opcode, 2);
-                    *self.addr(opcode / 10000) = if self.register[0] < self.register[1] {
-                        1
-                    } else {
-                        0
-                    };
-                }
-                8 => {
-                    // equals
-                    self.read(opcode, 2);
-                    *self.addr(opcode / 10000) = if self.register[0] == self.register[1] {
-                        1
-                    } else {
-                        0
-                    };
-                }
-                9 => {
-                    // adjust relative base
-                    self.read(opcode, 1);
-                    self.base += self.register[0];
+            }
+            4 => {
+                // read output
+                self.read(opcode, 1)?;
+                return Ok(Step::Output(self.register[0]));
+            }
+            5 => {
+                // jump-if-true
+                self.read(opcode, 2)?;
+                if self.register[0] != 0 {
+                    self.ip = self.address(self.register[1])?;
                 }
-                99 => {
-                    // halt
-                    self.halted = true;
-                    break None;
+            }
+            6 => {
+                // jump-if-false
+                self.read(opcode, 2)?;
+                if self.register[0] == 0 {
+                    self.ip = self.address(self.register[1])?;
                 }
-                _ => self.panic("illegal instruction", 1),
+            }
+            7 => {
+                // less than
+                self.read(opcode, 2)?;
+                let value = i64::from(self.register[0] < self.register[1]);
+                self.write(opcode, opcode / 10000, value)?;
+            }
+            8 => {
+                // equals
+                self.read(opcode, 2)?;
+                let value = i64::from(self.register[0] == self.register[1]);
+                self.write(opcode, opcode / 10000, value)?;
+            }
+            9 => {
+                // adjust relative base
+                self.read(opcode, 1)?;
+                self.base = self
+                    .base
+                    .checked_add(self.register[0])
+                    .ok_or(FaultKind::Overflow)?;
+            }
+            99 => {
+                // halt
+                self.halted = true;
+                return Ok(Step::Halted);
+            }
+            _ => {
+                let opcodes = self.opcodes;
+                return match opcodes.and_then(|opcodes| opcodes.get(opcode % 100)) {
+                    Some(custom) => self.custom(opcode, custom),
+                    None => Err(FaultKind::IllegalInstruction(opcode)),
+                };
+            }
+        }
+        Ok(Step::Continue)
+    }
+
+    fn custom(&mut self, opcode: i64, custom: &Custom) -> Result<Step, FaultKind> {
+        let mut params = [0; 3];
+        let mut modes = opcode / 100;
+        for (kind, param) in custom.params.iter().zip(params.iter_mut()) {
+            let value = self.pop()?;
+            *param = match (kind, modes % 10) {
+                (Kind::Value, 0) => self.program[self.address(value)?],
+                (Kind::Value, 1) | (Kind::Address, 0) => value,
+                (Kind::Value, 2) => self.program[self.relative(value)?],
+                (Kind::Address, 2) => self.base.checked_add(value).ok_or(FaultKind::Overflow)?,
+                _ => return Err(FaultKind::IllegalMode(opcode)),
+            };
+            modes /= 10;
+        }
+
+        let mut context = Context {
+            memory: self.program,
+            ip: self.ip,
+            base: self.base,
+            write: None,
+        };
+        let step = (custom.execute)(&mut context, &params[..custom.params.len()])?;
+        let Context {
+            ip, base, write, ..
+        } = context;
+        if let Some((addr, value)) = write {
+            let old = core::mem::replace(&mut self.program[addr], value);
+            self.last_write = Some((addr, old));
+        }
+        self.ip = ip;
+        self.base = base;
+        match step {
+            Step::Halted => self.halted = true,
+            Step::Blocked => panic!("custom instruction {} blocked", custom.name),
+            Step::Continue | Step::Output(_) => {}
+        }
+        Ok(step)
+    }
+}
+
+impl Iterator for Runner<'_> {
+    type Item = Poll<i64>;
+
+    fn next(&mut self) -> Option<Poll<i64>> {
+        if self.cache.is_some() {
+            return self.next_cached();
+        }
+        loop {
+            let step = self.try_step_uncached();
+            match step.unwrap_or_else(|fault| panic!("{}", fault)) {
+                Step::Continue => {}
+                Step::Output(value) => break Some(Poll::Ready(value)),
+                Step::Blocked => break Some(Poll::Pending),
+                Step::Halted => break None,
             }
         }
     }
@@ -221,6 +544,9 @@ impl Debug for Runner<'_> {
             .field("halted", &self.halted)
             .field("input", &self.input)
             .field("register", &self.register)
+            .field("last_write", &self.last_write)
+            .field("validation", &self.validation)
+            .field("caching", &self.cache.is_some())
             .finish()
     }
 }
@@ -231,7 +557,7 @@ pub struct FullRunner<'a, I> {
     iter: I,
 }
 
-impl<'a, I, T> FullRunner<'a, I>
+impl<I, T> FullRunner<'_, I>
 where
     I: Iterator<Item = T>,
     T: Into<i64>,
@@ -241,7 +567,7 @@ where
     }
 }
 
-impl<'a, I, T> Iterator for FullRunner<'_, I>
+impl<I, T> Iterator for FullRunner<'_, I>
 where
     I: Iterator<Item = T>,
     T: Into<i64>,
@@ -256,7 +582,7 @@ where
                     if let Some(input) = self.iter.next() {
                         self.runner.input(input.into());
                     } else {
-                        self.runner.panic("program blocked on input", 0);
+                        self.runner.panic("program blocked on input");
                     }
                 }
                 None => break None,
@@ -347,7 +673,7 @@ fn test() {
 
     // day 9
     let mut a = [0; 102];
-    &a[..16].copy_from_slice(&[
+    a[..16].copy_from_slice(&[
         109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
     ]);
     intcode_eq!(
@@ -357,9 +683,41 @@ fn test() {
     );
 
     intcode_eq!(
-        [1102, 34915192, 34915192, 7, 4, 7, 99, 0],
+        [1102, 34_915_192, 34_915_192, 7, 4, 7, 99, 0],
         [],
-        [1219070632396864]
+        [1_219_070_632_396_864]
     );
-    intcode_eq!([104, 1125899906842624, 99], [], [1125899906842624]);
+    intcode_eq!(
+        [104, 1_125_899_906_842_624, 99],
+        [],
+        [1_125_899_906_842_624]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_input_queue() {
+    // read two numbers, output their sum
+    let mut program = [3, 11, 3, 12, 1, 11, 12, 13, 4, 13, 99, 0, 0, 0];
+    let mut runner = Runner::new(&mut program);
+    runner.extend_input(alloc::vec![3, 4, 5]);
+    assert_eq!(runner.next(), Some(Poll::Ready(7)));
+    assert_eq!(
+        runner
+            .pending_input()
+            .iter()
+            .collect::<alloc::vec::Vec<_>>(),
+        [&5]
+    );
+    assert_eq!(runner.next(), None);
+
+    // an empty queue blocks on the input instruction until more input arrives
+    let mut program = [3, 11, 3, 12, 1, 11, 12, 13, 4, 13, 99, 0, 0, 0];
+    let mut runner = Runner::new(&mut program);
+    runner.input(3);
+    assert_eq!(runner.next(), Some(Poll::Pending));
+    assert_eq!(runner.ip(), 2);
+    assert_eq!(runner.next(), Some(Poll::Pending));
+    runner.input(4);
+    assert_eq!(runner.next(), Some(Poll::Ready(7)));
 }
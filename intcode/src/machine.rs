@@ -0,0 +1,267 @@
+use crate::opcodes::Opcodes;
+use crate::validate::Validation;
+use crate::{Fault, Runner, State, Step};
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use core::task::Poll;
+
+/// An intcode machine that owns its memory.
+///
+/// Unlike a [`Runner`], a `Machine` has no lifetime, so it can be kept in collections and moved
+/// into closures or other threads. It keeps the program it was created from as a shared image:
+/// cloning a machine doesn't copy the image, and [`reset`](Machine::reset) restores memory from it
+/// without allocating.
+#[derive(Clone)]
+pub struct Machine {
+    image: Arc<[i64]>,
+    memory: Vec<i64>,
+    state: State,
+    opcodes: Option<Arc<Opcodes>>,
+}
+
+impl Machine {
+    pub fn new(image: impl Into<Arc<[i64]>>) -> Machine {
+        let image = image.into();
+        Machine::with_memory(image.clone(), image.len())
+    }
+
+    /// Creates a machine with `size` words of memory, the rest of which is zero after the program.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the program doesn't fit in `size` words.
+    pub fn with_memory(image: impl Into<Arc<[i64]>>, size: usize) -> Machine {
+        let image = image.into();
+        assert!(image.len() <= size, "program doesn't fit in memory");
+        let mut memory = Vec::with_capacity(size);
+        memory.extend_from_slice(&image);
+        memory.resize(size, 0);
+        Machine {
+            image,
+            memory,
+            state: State::default(),
+            opcodes: None,
+        }
+    }
+
+    /// The program the machine was created from.
+    pub fn image(&self) -> &Arc<[i64]> {
+        &self.image
+    }
+
+    /// Puts the machine back the way it was when it was created, apart from its validation mode,
+    /// its custom instructions and whether it caches blocks.
+    pub fn reset(&mut self) {
+        let (program, rest) = self.memory.split_at_mut(self.image.len());
+        program.copy_from_slice(&self.image);
+        for word in rest {
+            *word = 0;
+        }
+        let validation = self.state.validation;
+        let mut cache = self.state.cache.take();
+        if let Some(cache) = &mut cache {
+            cache.clear();
+        }
+        self.state = State {
+            validation,
+            cache,
+            ..State::default()
+        };
+    }
+
+    /// Resets the machine, then patches its memory; see [`patch`](Machine::patch).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a patch is outside of memory.
+    pub fn reset_with(&mut self, patches: &[(usize, i64)]) {
+        self.reset();
+        self.patch(patches);
+    }
+
+    /// Writes each `(address, value)` pair to memory, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a patch is outside of memory.
+    pub fn patch(&mut self, patches: &[(usize, i64)]) {
+        for (addr, value) in patches {
+            self.memory[*addr] = *value;
+        }
+        self.clear_cache();
+    }
+
+    /// Replaces the program with a new one and resets the machine, reusing its memory. Memory is
+    /// grown if the new program doesn't fit.
+    pub fn reload(&mut self, image: impl Into<Arc<[i64]>>) {
+        self.image = image.into();
+        if self.memory.len() < self.image.len() {
+            self.memory.resize(self.image.len(), 0);
+        }
+        self.reset();
+    }
+
+    pub fn ip(&self) -> usize {
+        self.state.ip
+    }
+
+    pub fn base(&self) -> i64 {
+        self.state.base
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.state.halted
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut [i64] {
+        self.clear_cache();
+        &mut self.memory
+    }
+
+    /// See [`Runner::last_write`].
+    pub fn last_write(&self) -> Option<(usize, i64)> {
+        self.state.last_write
+    }
+
+    /// See [`Runner::input`].
+    pub fn input(&mut self, input: i64) {
+        self.state.input.push_back(input);
+    }
+
+    pub fn extend_input<I: IntoIterator<Item = i64>>(&mut self, inputs: I) {
+        self.state.input.extend(inputs);
+    }
+
+    pub fn pending_input(&self) -> &VecDeque<i64> {
+        &self.state.input
+    }
+
+    pub fn clear_input(&mut self) {
+        self.state.input.clear();
+    }
+
+    pub fn set_validation(&mut self, validation: Validation) {
+        self.state.validation = validation;
+        self.clear_cache();
+    }
+
+    /// See [`Runner::set_caching`].
+    pub fn set_caching(&mut self, caching: bool) {
+        self.with_runner(|runner| runner.set_caching(caching));
+    }
+
+    fn clear_cache(&mut self) {
+        if let Some(cache) = &mut self.state.cache {
+            cache.clear();
+        }
+    }
+
+    /// Makes the machine understand the custom instructions in `opcodes`, as a runner created with
+    /// [`Runner::with_opcodes`] does. Clones of the machine share the table.
+    pub fn set_opcodes(&mut self, opcodes: impl Into<Arc<Opcodes>>) {
+        self.opcodes = Some(opcodes.into());
+        self.clear_cache();
+    }
+
+    /// Borrows the machine as a [`Runner`] for the duration of `f`.
+    pub fn with_runner<'m, T>(&'m mut self, f: impl FnOnce(&mut Runner<'m>) -> T) -> T {
+        let state = core::mem::take(&mut self.state);
+        let mut runner = Runner::resume(&mut self.memory, state, self.opcodes.as_deref());
+        let result = f(&mut runner);
+        self.state = runner.into_state();
+        result
+    }
+
+    /// See [`Runner::step`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instruction faults.
+    pub fn step(&mut self) -> Step {
+        self.with_runner(Runner::step)
+    }
+
+    /// See [`Runner::try_step`].
+    pub fn try_step(&mut self) -> Result<Step, Fault> {
+        self.with_runner(Runner::try_step)
+    }
+
+    /// See [`Runner::run`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the program blocks on input or faults.
+    pub fn run(&mut self) {
+        self.with_runner(Runner::run);
+    }
+}
+
+impl Iterator for Machine {
+    type Item = Poll<i64>;
+
+    fn next(&mut self) -> Option<Poll<i64>> {
+        self.with_runner(Runner::next)
+    }
+}
+
+impl Debug for Machine {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Machine")
+            .field("ip", &self.state.ip)
+            .field("base", &self.state.base)
+            .field("halted", &self.state.halted)
+            .field("input", &self.state.input)
+            .field("memory", &self.memory.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_machine() {
+    use crate::PollExt;
+
+    // read two numbers, output their sum
+    let mut machine = Machine::with_memory(&[3, 11, 3, 12, 1, 11, 12, 13, 4, 13, 99][..], 14);
+    machine.input(3);
+    assert_eq!(machine.next(), Some(Poll::Pending));
+    machine.input(4);
+    assert_eq!(machine.next().unwrap().unwrap(), 7);
+    assert_eq!(machine.next(), None);
+    assert!(machine.is_halted());
+    assert_eq!(machine.memory()[11..], [3, 4, 7]);
+
+    // clones share the image but not memory
+    let mut clone = machine.clone();
+    assert!(Arc::ptr_eq(machine.image(), clone.image()));
+    clone.reset();
+    assert_eq!(clone.memory()[11..], [0, 0, 0]);
+    assert_eq!(machine.memory()[11..], [3, 4, 7]);
+    assert!(!clone.is_halted());
+
+    // machines can be moved to other threads
+    let _: &dyn Send = &clone;
+
+    clone.input(10);
+    clone.next();
+    clone.input(20);
+    assert_eq!(clone.next().unwrap().unwrap(), 30);
+
+    // patch the second input instruction into an output of the first input
+    clone.reset_with(&[(2, 4), (3, 11), (4, 99)]);
+    clone.input(5);
+    assert_eq!(clone.next().unwrap().unwrap(), 5);
+    assert_eq!(clone.next(), None);
+    assert_eq!(clone.pending_input().len(), 0);
+
+    // output 42, in more memory than there was
+    clone.reload(&[104, 42, 99, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0][..]);
+    assert_eq!(clone.memory().len(), 16);
+    assert_eq!(clone.next().unwrap().unwrap(), 42);
+}
@@ -0,0 +1,261 @@
+//! Custom instructions, for experimenting with extended instruction sets.
+//!
+//! The standard instructions are always executed directly; a [`Runner`](crate::Runner) only looks
+//! in its [`Opcodes`] table for opcodes it doesn't recognise, so programs that stick to the
+//! standard set pay nothing for the table being there.
+
+use crate::{FaultKind, Step};
+use core::convert::TryFrom;
+use core::fmt::{self, Display};
+
+/// The kind of a parameter, which decides what an instruction receives for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// The value of the parameter, resolved according to its mode.
+    Value,
+    /// The address the parameter refers to. Immediate mode is not allowed.
+    Address,
+}
+
+/// A custom instruction.
+#[derive(Debug, Clone, Copy)]
+pub struct Custom {
+    /// The opcode, which goes in the low two digits of the instruction word.
+    pub code: i64,
+    pub name: &'static str,
+    pub params: &'static [Kind],
+    /// Executes the instruction, given the resolved parameters. `Step::Blocked` may not be
+    /// returned.
+    pub execute: fn(&mut Context<'_>, &[i64]) -> Result<Step, FaultKind>,
+}
+
+/// What a custom instruction can see and change while it executes.
+#[derive(Debug)]
+pub struct Context<'a> {
+    pub(crate) memory: &'a mut [i64],
+    pub(crate) ip: usize,
+    pub(crate) base: i64,
+    pub(crate) write: Option<(usize, i64)>,
+}
+
+impl Context<'_> {
+    fn address(&self, addr: i64) -> Result<usize, FaultKind> {
+        usize::try_from(addr)
+            .ok()
+            .filter(|addr| *addr < self.memory.len())
+            .ok_or(FaultKind::IllegalAddress(addr))
+    }
+
+    pub fn read(&self, addr: i64) -> Result<i64, FaultKind> {
+        let addr = self.address(addr)?;
+        Ok(match self.write {
+            Some((write, value)) if write == addr => value,
+            _ => self.memory[addr],
+        })
+    }
+
+    /// Writes to memory. The write only happens if the instruction doesn't fault.
+    ///
+    /// # Panics
+    ///
+    /// Like the standard instructions, custom instructions can write to at most one address.
+    pub fn write(&mut self, addr: i64, value: i64) -> Result<(), FaultKind> {
+        assert!(
+            self.write.is_none(),
+            "custom instructions can only write once"
+        );
+        self.write = Some((self.address(addr)?, value));
+        Ok(())
+    }
+
+    /// The address of the next instruction.
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn jump(&mut self, addr: i64) -> Result<(), FaultKind> {
+        self.ip = self.address(addr)?;
+        Ok(())
+    }
+
+    pub fn base(&self) -> i64 {
+        self.base
+    }
+
+    pub fn set_base(&mut self, base: i64) {
+        self.base = base;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterError {
+    /// The opcode belongs to a standard instruction.
+    Reserved(i64),
+    /// The opcode doesn't fit in two digits.
+    OutOfRange(i64),
+    Duplicate(i64),
+    /// Only as many parameters as the standard instructions have (three) are supported.
+    TooManyParams(i64),
+}
+
+impl Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegisterError::Reserved(code) => write!(f, "opcode {} is a standard instruction", code),
+            RegisterError::OutOfRange(code) => write!(f, "opcode {} is out of range", code),
+            RegisterError::Duplicate(code) => write!(f, "opcode {} is already registered", code),
+            RegisterError::TooManyParams(code) => {
+                write!(f, "opcode {} has more than three parameters", code)
+            }
+        }
+    }
+}
+
+/// A table of custom instructions.
+#[derive(Debug, Clone)]
+pub struct Opcodes {
+    slots: [Option<Custom>; 100],
+}
+
+impl Opcodes {
+    pub fn new() -> Opcodes {
+        Opcodes { slots: [None; 100] }
+    }
+
+    pub fn register(&mut self, custom: Custom) -> Result<(), RegisterError> {
+        let code = custom.code;
+        if crate::decode::Opcode::from_word(code).is_some() && code < 100 {
+            return Err(RegisterError::Reserved(code));
+        }
+        let slot = usize::try_from(code)
+            .ok()
+            .and_then(|i| self.slots.get_mut(i))
+            .filter(|_| code > 0)
+            .ok_or(RegisterError::OutOfRange(code))?;
+        if slot.is_some() {
+            return Err(RegisterError::Duplicate(code));
+        }
+        if custom.params.len() > 3 {
+            return Err(RegisterError::TooManyParams(code));
+        }
+        *slot = Some(custom);
+        Ok(())
+    }
+
+    /// Looks up a custom instruction by opcode.
+    pub fn get(&self, code: i64) -> Option<&Custom> {
+        self.slots.get(usize::try_from(code).ok()?)?.as_ref()
+    }
+}
+
+impl Default for Opcodes {
+    fn default() -> Opcodes {
+        Opcodes::new()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test() {
+    use crate::{PollExt, Runner};
+    use alloc::vec::Vec;
+    use Kind::{Address, Value};
+
+    let mut opcodes = Opcodes::new();
+    opcodes
+        .register(Custom {
+            code: 10,
+            name: "div",
+            params: &[Value, Value, Address],
+            execute: |cx, p| {
+                let value = p[0].checked_div(p[1]).ok_or(FaultKind::Overflow)?;
+                cx.write(p[2], value)?;
+                Ok(Step::Continue)
+            },
+        })
+        .unwrap();
+    // call pushes the return address onto a stack at the relative base; ret pops it
+    opcodes
+        .register(Custom {
+            code: 11,
+            name: "call",
+            params: &[Value],
+            execute: |cx, p| {
+                cx.write(cx.base(), i64::try_from(cx.ip()).unwrap())?;
+                cx.set_base(cx.base() + 1);
+                cx.jump(p[0])?;
+                Ok(Step::Continue)
+            },
+        })
+        .unwrap();
+    opcodes
+        .register(Custom {
+            code: 12,
+            name: "ret",
+            params: &[],
+            execute: |cx, _| {
+                cx.set_base(cx.base() - 1);
+                cx.jump(cx.read(cx.base())?)?;
+                Ok(Step::Continue)
+            },
+        })
+        .unwrap();
+
+    assert_eq!(
+        opcodes.register(Custom {
+            code: 1,
+            ..*opcodes.get(10).unwrap()
+        }),
+        Err(RegisterError::Reserved(1))
+    );
+    assert_eq!(
+        opcodes.register(*opcodes.get(10).unwrap()),
+        Err(RegisterError::Duplicate(10))
+    );
+    assert_eq!(
+        opcodes.register(Custom {
+            code: 100,
+            ..*opcodes.get(10).unwrap()
+        }),
+        Err(RegisterError::OutOfRange(100))
+    );
+
+    let mut program = [
+        109, 30, // arb 30
+        3, 20, // in [20]
+        111, 13, // call 13
+        4, 20, // out [20]
+        111, 13, // call 13
+        4, 20, // out [20]
+        99, // hlt
+        1010, 20, 2, 20, // 13: div [20], 2, [20]
+        12, // ret
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // 18: data and stack
+    ];
+    let mut runner = Runner::with_opcodes(&mut program, &opcodes);
+    runner.input(100);
+    assert_eq!(runner.map(PollExt::unwrap).collect::<Vec<_>>(), [50, 25]);
+
+    // machines keep their table when reset, and share it with their clones
+    let mut machine = crate::Machine::new(&program[..]);
+    machine.set_opcodes(opcodes.clone());
+    machine.input(100);
+    assert_eq!(machine.next().unwrap().unwrap(), 50);
+    machine.reset();
+    let mut clone = machine.clone();
+    clone.input(8);
+    assert_eq!(clone.map(PollExt::unwrap).collect::<Vec<_>>(), [4, 2]);
+
+    // unregistered opcodes still fault, as do custom instructions that fault
+    let mut program = [1010, 1, 0, 3];
+    let mut runner = Runner::with_opcodes(&mut program, &opcodes);
+    assert_eq!(runner.try_step().unwrap_err().kind, FaultKind::Overflow);
+    assert_eq!(runner.ip(), 0);
+    assert_eq!(runner.memory(), [1010, 1, 0, 3]);
+    let mut program = [13];
+    let mut runner = Runner::with_opcodes(&mut program, &opcodes);
+    assert_eq!(
+        runner.try_step().unwrap_err().kind,
+        FaultKind::IllegalInstruction(13)
+    );
+}
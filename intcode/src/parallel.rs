@@ -0,0 +1,134 @@
+//! Running many machines at once on a pool of threads.
+//!
+//! Jobs are handed out to the workers in order, one at a time, and results are put back in order
+//! afterwards, so a batch gives the same results however many threads run it.
+
+use crate::Machine;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::num::NonZeroUsize;
+use std::panic;
+use std::thread;
+
+/// A program to run with many different configurations.
+///
+/// Each job gets a [`Machine`] freshly [`reset`](Machine::reset) to the program, along with its
+/// configuration, and can patch memory, give inputs and run it (or clones of it) however it likes.
+#[derive(Debug, Clone)]
+pub struct Batch {
+    machine: Machine,
+    threads: usize,
+}
+
+impl Batch {
+    /// Creates a batch that runs on as many threads as there are CPUs.
+    pub fn new(machine: Machine) -> Batch {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        Batch::with_threads(machine, threads)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero.
+    pub fn with_threads(machine: Machine, threads: usize) -> Batch {
+        assert!(threads > 0, "need at least one thread");
+        Batch { machine, threads }
+    }
+
+    /// Runs a job for each configuration, returning the results in the same order.
+    pub fn map<C, T, F>(&self, configs: &[C], job: F) -> Vec<T>
+    where
+        C: Sync,
+        T: Send,
+        F: Fn(&mut Machine, &C) -> T + Sync,
+    {
+        self.run(configs, |machine, config| Some(job(machine, config)), false)
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
+
+    /// Runs jobs until one returns `Some`, returning its index and result.
+    ///
+    /// If more than one job would return `Some`, the first of them (by index) wins, just as if the
+    /// jobs had been run one after another. Jobs after it that haven't started yet are skipped.
+    pub fn find<C, T, F>(&self, configs: &[C], job: F) -> Option<(usize, T)>
+    where
+        C: Sync,
+        T: Send,
+        F: Fn(&mut Machine, &C) -> Option<T> + Sync,
+    {
+        self.run(configs, job, true).into_iter().next()
+    }
+
+    fn run<C, T, F>(&self, configs: &[C], job: F, stop: bool) -> Vec<(usize, T)>
+    where
+        C: Sync,
+        T: Send,
+        F: Fn(&mut Machine, &C) -> Option<T> + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        // the lowest index found so far, when stopping early
+        let found = AtomicUsize::new(usize::MAX);
+        let worker = || {
+            let mut machine = self.machine.clone();
+            let mut results = Vec::new();
+            loop {
+                // Jobs are taken in order, so once a job is found every job before it has already
+                // been taken, and the ones after it can be skipped.
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= configs.len() || i > found.load(Ordering::Relaxed) {
+                    break results;
+                }
+                machine.reset();
+                if let Some(result) = job(&mut machine, &configs[i]) {
+                    if stop {
+                        found.fetch_min(i, Ordering::Relaxed);
+                    }
+                    results.push((i, result));
+                }
+            }
+        };
+
+        let mut results = thread::scope(|scope| {
+            let workers = (0..self.threads.min(configs.len()))
+                .map(|_| scope.spawn(worker))
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .flat_map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|err| panic::resume_unwind(err))
+                })
+                .collect::<Vec<_>>()
+        });
+        results.sort_unstable_by_key(|(i, _)| *i);
+        results
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_batch() {
+    use crate::PollExt;
+
+    // output the input times three
+    let machine = Machine::new(&[3, 9, 1002, 9, 3, 9, 4, 9, 99, 0][..]);
+    let inputs = (0..1000).collect::<Vec<i64>>();
+    for threads in 1..=4 {
+        let batch = Batch::with_threads(machine.clone(), threads);
+        let outputs = batch.map(&inputs, |machine, input| {
+            machine.input(*input);
+            machine.next().unwrap().unwrap()
+        });
+        assert_eq!(outputs, inputs.iter().map(|i| i * 3).collect::<Vec<_>>());
+
+        let found = batch.find(&inputs, |machine, input| {
+            machine.input(*input);
+            let output = machine.next().unwrap().unwrap();
+            Some(output).filter(|output| output % 7 == 6)
+        });
+        assert_eq!(found, Some((2, 6)));
+    }
+}
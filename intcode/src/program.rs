@@ -0,0 +1,442 @@
+//! Intcode program files.
+//!
+//! A program file is the comma-separated list of integers the puzzles come as, optionally preceded
+//! by a header of directives, one per line:
+//!
+//! ```text
+//! # comments run to the end of the line
+//! .memory 4096        # words of memory the program needs
+//! .io triples         # how it talks: numeric, ascii or triples
+//! .patch 0 2          # write 2 to address 0 before running
+//! .answer part1 247   # a known answer
+//! 1,0,0,3,99
+//! ```
+//!
+//! The list of integers may be split across lines. A plain list, with no header, is a valid
+//! program file.
+
+use crate::Machine;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+/// How a program expects to be talked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Io {
+    /// Each input and output is a number.
+    Numeric,
+    /// Inputs and outputs are characters of text.
+    Ascii,
+    /// Outputs come in groups of three, like `x, y, tile`.
+    Triples,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Program {
+    pub code: Vec<i64>,
+    /// The amount of memory the program needs, if it needs more than its own length.
+    pub memory: Option<usize>,
+    pub io: Option<Io>,
+    /// Values to write to memory before running, as `(address, value)` pairs.
+    pub patches: Vec<(usize, i64)>,
+    /// Known answers, as `(name, answer)` pairs.
+    pub answers: Vec<(String, String)>,
+}
+
+impl Program {
+    pub fn parse(source: &str) -> Result<Program, ParseError> {
+        Parser::default().parse(source)
+    }
+
+    /// The code with the patches applied.
+    pub fn image(&self) -> Vec<i64> {
+        let mut image = self.code.clone();
+        for (addr, value) in &self.patches {
+            if *addr >= image.len() {
+                image.resize(addr + 1, 0);
+            }
+            image[*addr] = *value;
+        }
+        image
+    }
+
+    /// The size of memory to run the program in.
+    pub fn memory_size(&self) -> usize {
+        self.memory.unwrap_or_else(|| self.image().len())
+    }
+
+    /// Creates a machine for the program, with its patches applied and as much memory as it
+    /// needs. Resetting the machine keeps the patches.
+    pub fn machine(&self) -> Machine {
+        Machine::with_memory(self.image(), self.memory_size())
+    }
+
+    pub fn answer(&self, name: &str) -> Option<&str> {
+        self.answers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, answer)| answer.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the error is on, counting from 1.
+    pub line: usize,
+    /// The column the error starts at, counting characters from 1.
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    UnknownDirective(String),
+    DuplicateDirective(String),
+    DirectiveAfterCode,
+    MissingArgument(&'static str),
+    UnexpectedArgument(String),
+    InvalidNumber(String),
+    InvalidIo(String),
+    NoCode,
+    /// The program doesn't fit in the memory size it asks for.
+    MemoryTooSmall {
+        needed: usize,
+    },
+    /// A patch writes outside of the program's memory, which is only as long as the program
+    /// itself without a `.memory` directive.
+    PatchOutOfRange(usize),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::UnknownDirective(name) => write!(f, "unknown directive .{}", name),
+            ParseErrorKind::DuplicateDirective(name) => write!(f, "duplicate directive .{}", name),
+            ParseErrorKind::DirectiveAfterCode => write!(f, "directives must come before code"),
+            ParseErrorKind::MissingArgument(what) => write!(f, "expected {}", what),
+            ParseErrorKind::UnexpectedArgument(arg) => write!(f, "unexpected argument {:?}", arg),
+            ParseErrorKind::InvalidNumber(s) => write!(f, "invalid number {:?}", s),
+            ParseErrorKind::InvalidIo(s) => write!(
+                f,
+                "unknown I/O protocol {:?} (expected numeric, ascii or triples)",
+                s
+            ),
+            ParseErrorKind::NoCode => write!(f, "no code"),
+            ParseErrorKind::MemoryTooSmall { needed } => {
+                write!(f, "memory too small, the program needs {} words", needed)
+            }
+            ParseErrorKind::PatchOutOfRange(addr) => {
+                write!(f, "patch address {} is outside of memory", addr)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+#[derive(Default)]
+struct Parser {
+    program: Program,
+    /// Where each directive that needs checking once the code is known was, as `(line, column)`.
+    memory_at: Option<(usize, usize)>,
+    patches_at: Vec<(usize, usize)>,
+    lines: usize,
+}
+
+impl Parser {
+    fn parse(mut self, source: &str) -> Result<Program, ParseError> {
+        for (i, line) in source.lines().enumerate() {
+            let line_no = i + 1;
+            self.lines = line_no;
+            let line = line.split('#').next().unwrap_or_default();
+            let words = words(line);
+            match words.first() {
+                None => {}
+                Some((at, word)) if word.starts_with('.') => {
+                    if !self.program.code.is_empty() {
+                        return Err(error(
+                            line_no,
+                            line,
+                            *at,
+                            ParseErrorKind::DirectiveAfterCode,
+                        ));
+                    }
+                    self.directive(line_no, line, &words)?;
+                }
+                Some(_) => self.code(line_no, line)?,
+            }
+        }
+        self.finish()
+    }
+
+    fn directive(
+        &mut self,
+        line_no: usize,
+        line: &str,
+        words: &[(usize, &str)],
+    ) -> Result<(), ParseError> {
+        let (at, name) = (words[0].0, &words[0].1[1..]);
+        let mut args = Args {
+            line_no,
+            line,
+            words: words[1..].iter(),
+        };
+        let duplicate = || {
+            error(
+                line_no,
+                line,
+                at,
+                ParseErrorKind::DuplicateDirective(name.to_string()),
+            )
+        };
+        match name {
+            "memory" => {
+                if self.program.memory.is_some() {
+                    return Err(duplicate());
+                }
+                self.program.memory = Some(args.number("a memory size")?.1);
+                self.memory_at = Some((line_no, column(line, at)));
+            }
+            "io" => {
+                if self.program.io.is_some() {
+                    return Err(duplicate());
+                }
+                let (at, io) = args.word("an I/O protocol")?;
+                self.program.io = Some(match io {
+                    "numeric" => Io::Numeric,
+                    "ascii" => Io::Ascii,
+                    "triples" => Io::Triples,
+                    _ => {
+                        return Err(error(
+                            line_no,
+                            line,
+                            at,
+                            ParseErrorKind::InvalidIo(io.to_string()),
+                        ))
+                    }
+                });
+            }
+            "patch" => {
+                let (addr_at, addr) = args.number("an address")?;
+                let (_, value) = args.number("a value")?;
+                self.program.patches.push((addr, value));
+                self.patches_at.push((line_no, column(line, addr_at)));
+            }
+            "answer" => {
+                let (_, name) = args.word("an answer name")?;
+                let (_, answer) = args.word("an answer")?;
+                self.program
+                    .answers
+                    .push((name.to_string(), answer.to_string()));
+            }
+            _ => {
+                return Err(error(
+                    line_no,
+                    line,
+                    at,
+                    ParseErrorKind::UnknownDirective(name.to_string()),
+                ))
+            }
+        }
+        args.end()
+    }
+
+    fn code(&mut self, line_no: usize, line: &str) -> Result<(), ParseError> {
+        let mut at = 0;
+        let mut tokens = line.split(',').peekable();
+        while let Some(token) = tokens.next() {
+            let trimmed = token.trim();
+            let start = at + (token.len() - token.trim_start().len());
+            // a trailing comma carries the list on to the next line
+            if !(trimmed.is_empty() && tokens.peek().is_none()) {
+                let value = trimmed.parse().map_err(|_| {
+                    error(
+                        line_no,
+                        line,
+                        start,
+                        ParseErrorKind::InvalidNumber(trimmed.to_string()),
+                    )
+                })?;
+                self.program.code.push(value);
+            }
+            at += token.len() + 1;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Program, ParseError> {
+        let program = self.program;
+        if program.code.is_empty() {
+            return Err(ParseError {
+                line: self.lines + 1,
+                column: 1,
+                kind: ParseErrorKind::NoCode,
+            });
+        }
+        if let (Some(memory), Some((line, column))) = (program.memory, self.memory_at) {
+            if memory < program.code.len() {
+                return Err(ParseError {
+                    line,
+                    column,
+                    kind: ParseErrorKind::MemoryTooSmall {
+                        needed: program.code.len(),
+                    },
+                });
+            }
+        }
+        let size = program.memory.unwrap_or(program.code.len());
+        for ((addr, _), (line, column)) in program.patches.iter().zip(self.patches_at) {
+            if *addr >= size {
+                return Err(ParseError {
+                    line,
+                    column,
+                    kind: ParseErrorKind::PatchOutOfRange(*addr),
+                });
+            }
+        }
+        Ok(program)
+    }
+}
+
+struct Args<'a, 'b> {
+    line_no: usize,
+    line: &'a str,
+    words: core::slice::Iter<'b, (usize, &'a str)>,
+}
+
+impl<'a> Args<'a, '_> {
+    fn word(&mut self, what: &'static str) -> Result<(usize, &'a str), ParseError> {
+        self.words.next().copied().ok_or_else(|| {
+            error(
+                self.line_no,
+                self.line,
+                self.line.trim_end().len(),
+                ParseErrorKind::MissingArgument(what),
+            )
+        })
+    }
+
+    fn number<T: FromStr>(&mut self, what: &'static str) -> Result<(usize, T), ParseError> {
+        let (at, word) = self.word(what)?;
+        let value = word.parse().map_err(|_| {
+            error(
+                self.line_no,
+                self.line,
+                at,
+                ParseErrorKind::InvalidNumber(word.to_string()),
+            )
+        })?;
+        Ok((at, value))
+    }
+
+    fn end(mut self) -> Result<(), ParseError> {
+        match self.words.next() {
+            Some((at, word)) => Err(error(
+                self.line_no,
+                self.line,
+                *at,
+                ParseErrorKind::UnexpectedArgument((*word).to_string()),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Splits a line into words, along with the byte offset each one starts at.
+fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in line
+        .char_indices()
+        .chain(core::iter::once((line.len(), ' ')))
+    {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                words.push((s, &line[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Converts a byte offset into a line to a column number.
+fn column(line: &str, at: usize) -> usize {
+    line[..at].chars().count() + 1
+}
+
+/// Makes an error at a byte offset into a line.
+fn error(line_no: usize, line: &str, at: usize, kind: ParseErrorKind) -> ParseError {
+    ParseError {
+        line: line_no,
+        column: column(line, at),
+        kind,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse() {
+    use crate::PollExt;
+    use alloc::vec;
+
+    let program = Program::parse("104,1125899906842624,99\n").unwrap();
+    assert_eq!(program.code, [104, 1_125_899_906_842_624, 99]);
+    assert_eq!(program.memory_size(), 3);
+
+    let program = Program::parse(
+        "# output whatever is at address 20\n\
+         .memory 32\n\
+         .io numeric\n\
+         .patch 20 7 # lucky\n\
+         .answer part1 7\n\
+         \n\
+         4, 20,\n\
+         99\n",
+    )
+    .unwrap();
+    assert_eq!(program.code, [4, 20, 99]);
+    assert_eq!(program.io, Some(Io::Numeric));
+    assert_eq!(program.answer("part1"), Some("7"));
+    let mut machine = program.machine();
+    assert_eq!(machine.memory().len(), 32);
+    assert_eq!(machine.next().unwrap().unwrap(), 7);
+    machine.reset();
+    assert_eq!(
+        machine.collect::<Vec<_>>(),
+        vec![core::task::Poll::Ready(7)]
+    );
+
+    let error = |source| Program::parse(source).unwrap_err().to_string();
+    assert_eq!(error("1,2,,99"), "1:5: invalid number \"\"");
+    assert_eq!(error("1, 2, x3, 99"), "1:7: invalid number \"x3\"");
+    assert_eq!(error(".memory\n99"), "1:8: expected a memory size");
+    assert_eq!(error(".memory 1 2\n99"), "1:11: unexpected argument \"2\"");
+    assert_eq!(
+        error("  .io text\n99"),
+        "1:7: unknown I/O protocol \"text\" (expected numeric, ascii or triples)"
+    );
+    assert_eq!(error(".stack 10\n99"), "1:1: unknown directive .stack");
+    assert_eq!(
+        error("99\n.memory 10"),
+        "2:1: directives must come before code"
+    );
+    assert_eq!(
+        error(".memory 2\n1,0,0,0,99"),
+        "1:1: memory too small, the program needs 5 words"
+    );
+    assert_eq!(
+        error(".memory 5\n.patch 5 1\n1,0,0,0,99"),
+        "2:8: patch address 5 is outside of memory"
+    );
+    assert_eq!(
+        error(".patch 1000000000000 1\n1,0,0,0,99"),
+        "1:8: patch address 1000000000000 is outside of memory"
+    );
+    assert_eq!(error("# nothing here\n"), "2:1: no code");
+}
@@ -0,0 +1,335 @@
+//! Recording and replaying what a program was given and what it gave back.
+//!
+//! A [`Recorder`] keeps a [`Session`] of every input given to a [`Machine`] or [`Runner`] and
+//! every output taken from it, in order. Replaying the session on a fresh machine gives it the
+//! same inputs at the same points and checks that it gives the same outputs, so a run that was
+//! driven by something unpredictable, like a random number generator or a person, can be checked
+//! again later.
+//!
+//! Sessions are saved as text, with runs of inputs or outputs on lines of their own:
+//!
+//! ```text
+//! # comments run to the end of the line
+//! in 1
+//! out 0
+//! in 4
+//! out 1, 2
+//! ```
+
+use crate::{Machine, Runner};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use core::task::Poll;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Input(i64),
+    Output(i64),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Session {
+    events: Vec<Event>,
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn push(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    pub fn parse(source: &str) -> Result<Session, ParseError> {
+        let mut session = Session::new();
+        for (i, line) in source.lines().enumerate() {
+            let error = |offset: usize, kind| ParseError {
+                line: i + 1,
+                column: line[..offset].chars().count() + 1,
+                kind,
+            };
+            let content = line.split('#').next().unwrap_or_default();
+            let trimmed = content.trim_start();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let start = content.len() - trimmed.len();
+            let (name, values) = trimmed.split_at(trimmed.find(' ').unwrap_or(trimmed.len()));
+            let event = match name {
+                "in" => Event::Input,
+                "out" => Event::Output,
+                _ => return Err(error(start, ParseErrorKind::UnknownEvent(name.to_string()))),
+            };
+
+            let mut offset = start + name.len();
+            for value in values.split(',') {
+                let trimmed = value.trim();
+                let at = offset + value.find(trimmed).unwrap_or(0);
+                offset += value.len() + 1;
+                if trimmed.is_empty() && values.trim().is_empty() {
+                    return Err(error(at, ParseErrorKind::MissingValue));
+                }
+                let value = trimmed
+                    .parse()
+                    .map_err(|_| error(at, ParseErrorKind::InvalidNumber(trimmed.to_string())))?;
+                session.push(event(value));
+            }
+        }
+        Ok(session)
+    }
+
+    /// Replays the session on `machine`, checking that it gives the same outputs.
+    ///
+    /// Only the outputs in the session are checked: the machine may go on to do more.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine faults.
+    pub fn replay(&self, machine: &mut impl Recordable) -> Result<(), Mismatch> {
+        for (i, event) in self.events.iter().enumerate() {
+            match *event {
+                Event::Input(value) => machine.input(value),
+                Event::Output(expected) => match machine.next() {
+                    Some(Poll::Ready(actual)) if actual == expected => {}
+                    actual => {
+                        return Err(Mismatch {
+                            event: i,
+                            expected,
+                            actual,
+                        })
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const WIDTH: usize = 16;
+
+        let mut last = None;
+        let mut count = 0;
+        for event in &self.events {
+            let (name, value) = match event {
+                Event::Input(value) => ("in", value),
+                Event::Output(value) => ("out", value),
+            };
+            if last == Some(name) && count < WIDTH {
+                write!(f, ", {}", value)?;
+                count += 1;
+            } else {
+                if last.is_some() {
+                    writeln!(f)?;
+                }
+                write!(f, "{} {}", name, value)?;
+                count = 1;
+            }
+            last = Some(name);
+        }
+        if last.is_some() {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the error is on, counting from 1.
+    pub line: usize,
+    /// The column the error starts at, counting characters from 1.
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A line that isn't `in` or `out`.
+    UnknownEvent(String),
+    MissingValue,
+    InvalidNumber(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::UnknownEvent(name) => {
+                write!(f, "unknown event {:?} (expected in or out)", name)
+            }
+            ParseErrorKind::MissingValue => write!(f, "expected a value"),
+            ParseErrorKind::InvalidNumber(s) => write!(f, "invalid number {:?}", s),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// An output that didn't match the one in a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The index of the event in the session.
+    pub event: usize,
+    pub expected: i64,
+    /// What the machine gave instead: a different output, a request for input, or nothing
+    /// because it halted.
+    pub actual: Option<Poll<i64>>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "event {}: expected output {}, ",
+            self.event, self.expected
+        )?;
+        match self.actual {
+            Some(Poll::Ready(value)) => write!(f, "got {}", value),
+            Some(Poll::Pending) => write!(f, "but the program blocked on input"),
+            None => write!(f, "but the program halted"),
+        }
+    }
+}
+
+/// Something that can be given inputs and iterated over for outputs, so that a [`Recorder`] can
+/// record it and a [`Session`] can be replayed on it.
+pub trait Recordable: Iterator<Item = Poll<i64>> {
+    fn input(&mut self, input: i64);
+}
+
+impl Recordable for Machine {
+    fn input(&mut self, input: i64) {
+        Machine::input(self, input);
+    }
+}
+
+impl Recordable for Runner<'_> {
+    fn input(&mut self, input: i64) {
+        Runner::input(self, input);
+    }
+}
+
+/// A [`Machine`] or [`Runner`] that records a [`Session`] as it runs.
+#[derive(Debug, Clone)]
+pub struct Recorder<M = Machine> {
+    machine: M,
+    session: Session,
+}
+
+impl<M: Recordable> Recorder<M> {
+    pub fn new(machine: M) -> Recorder<M> {
+        Recorder {
+            machine,
+            session: Session::new(),
+        }
+    }
+
+    pub fn machine(&self) -> &M {
+        &self.machine
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn into_session(self) -> Session {
+        self.session
+    }
+
+    /// See [`Machine::input`].
+    pub fn input(&mut self, input: i64) {
+        self.session.push(Event::Input(input));
+        self.machine.input(input);
+    }
+}
+
+impl<M: Recordable> Iterator for Recorder<M> {
+    type Item = Poll<i64>;
+
+    fn next(&mut self) -> Option<Poll<i64>> {
+        let next = self.machine.next();
+        if let Some(Poll::Ready(value)) = next {
+            self.session.push(Event::Output(value));
+        }
+        next
+    }
+}
+
+impl<M: Recordable> Recordable for Recorder<M> {
+    fn input(&mut self, input: i64) {
+        Recorder::input(self, input);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_session() {
+    use crate::PollExt;
+
+    // output the sum of each pair of inputs
+    let machine = Machine::new(&[3, 13, 3, 14, 1, 13, 14, 15, 4, 15, 1105, 1, 0, 0, 0, 0][..]);
+    let mut recorder = Recorder::new(machine.clone());
+    recorder.input(1);
+    recorder.input(2);
+    assert_eq!(recorder.next().unwrap().unwrap(), 3);
+    recorder.input(-4);
+    assert_eq!(recorder.next(), Some(Poll::Pending));
+    recorder.input(10);
+    assert_eq!(recorder.next().unwrap().unwrap(), 6);
+    let session = recorder.into_session();
+    assert_eq!(session.to_string(), "in 1, 2\nout 3\nin -4, 10\nout 6\n");
+    assert_eq!(Session::parse(&session.to_string()), Ok(session.clone()));
+    assert_eq!(session.replay(&mut machine.clone()), Ok(()));
+
+    let mut wrong = Session::parse("in 1, 2 # a comment\n\n  out 4\n").unwrap();
+    assert_eq!(
+        wrong
+            .replay(&mut machine.clone())
+            .map_err(|err| err.to_string()),
+        Err(String::from("event 2: expected output 4, got 3"))
+    );
+    wrong = Session::parse("in 1, 2\nout 3\nout 3").unwrap();
+    assert_eq!(
+        wrong.replay(&mut machine.clone()),
+        Err(Mismatch {
+            event: 3,
+            expected: 3,
+            actual: Some(Poll::Pending),
+        })
+    );
+
+    // runners over borrowed memory can be recorded too
+    let mut memory = machine.memory().to_vec();
+    let mut recorder = Recorder::new(Runner::new(&mut memory));
+    recorder.input(7);
+    recorder.input(8);
+    assert_eq!(recorder.next().unwrap().unwrap(), 15);
+    let session = recorder.into_session();
+    assert_eq!(session.to_string(), "in 7, 8\nout 15\n");
+    let mut memory = machine.memory().to_vec();
+    assert_eq!(session.replay(&mut Runner::new(&mut memory)), Ok(()));
+
+    assert_eq!(
+        Session::parse("in 1\n  inn 2").map_err(|err| err.to_string()),
+        Err(String::from(
+            "2:3: unknown event \"inn\" (expected in or out)"
+        ))
+    );
+    assert_eq!(
+        Session::parse("out 1, x").map_err(|err| err.to_string()),
+        Err(String::from("1:8: invalid number \"x\""))
+    );
+    assert_eq!(
+        Session::parse("out").map_err(|err| err.to_string()),
+        Err(String::from("1:4: expected a value"))
+    );
+}
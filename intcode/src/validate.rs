@@ -0,0 +1,135 @@
+//! Checking instruction words, at run time and before a program runs.
+//!
+//! By default a [`Runner`](crate::Runner) is lenient: like the original interpreter, it only looks
+//! at as many mode digits as an instruction has parameters and ignores the rest. In strict mode it
+//! checks the whole instruction word before executing it.
+
+use crate::decode::{self, Instruction, Mode};
+use crate::opcodes::Kind;
+use crate::{Fault, FaultKind};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Validation {
+    /// Mode digits beyond the instruction's parameters are ignored.
+    #[default]
+    Lenient,
+    /// Every mode digit must be valid for its parameter, and there must be no digits beyond the
+    /// last parameter.
+    Strict,
+}
+
+/// Checks the mode digits of an instruction word against the kinds of its parameters: each must
+/// be position, immediate (for values only) or relative, and there must be nothing left over.
+pub fn check_modes(word: i64, params: &[Kind]) -> Result<(), FaultKind> {
+    let mut modes = word / 100;
+    for kind in params {
+        match (kind, modes % 10) {
+            (_, 0 | 2) | (Kind::Value, 1) => {}
+            _ => return Err(FaultKind::IllegalMode(word)),
+        }
+        modes /= 10;
+    }
+    if modes == 0 {
+        Ok(())
+    } else {
+        Err(FaultKind::IllegalMode(word))
+    }
+}
+
+/// Scans a program for instructions that would fault, without running it.
+///
+/// The scan follows execution from address 0: it falls through from one instruction to the next,
+/// follows jumps whose targets are immediate, and assumes that calls return: an instruction that
+/// stores the address just past an unconditional jump, followed by that jump, is taken to be a
+/// call that comes back to that address. Other jumps through memory can't be followed, and
+/// nothing is known about code the program writes for itself, so this can miss faults; but
+/// everything it reports is at an address execution can reach (provided the program doesn't
+/// modify it first). Position-mode parameters that point outside of `program` are reported as
+/// well as bad instruction words.
+///
+/// Faults are returned in address order. Custom opcodes aren't understood and are reported as
+/// illegal instructions.
+pub fn preflight(program: &[i64], validation: Validation) -> Vec<Fault> {
+    let decode = match validation {
+        Validation::Lenient => Instruction::decode,
+        Validation::Strict => Instruction::decode_strict,
+    };
+    let mut faults = Vec::new();
+    decode::walk(program, decode, |ip, result| match result {
+        Ok(instruction) => {
+            if let Some(param) = instruction.params().iter().find(|param| {
+                param.mode == Mode::Position
+                    && usize::try_from(param.value).map_or(true, |addr| addr >= program.len())
+            }) {
+                faults.push(Fault {
+                    ip,
+                    kind: FaultKind::IllegalAddress(param.value),
+                });
+            }
+        }
+        Err(kind) => faults.push(Fault { ip, kind: *kind }),
+    });
+    faults.sort_by_key(|fault| fault.ip);
+    faults
+}
+
+#[cfg(test)]
+#[test]
+fn test_preflight() {
+    use crate::Runner;
+
+    let program = [
+        1105, 1, 7, // jt 1, 7
+        42, 42, 42, 42, // data, never executed
+        11_104, 5, // 7: out 5, with a stray mode digit
+        1006, 20, 16, // jf [20], 16
+        1101, 1, 1, -1, // add 1, 1, [-1]
+        99, // 16: hlt
+    ];
+    assert_eq!(
+        preflight(&program, Validation::Lenient),
+        [
+            Fault {
+                ip: 9,
+                kind: FaultKind::IllegalAddress(20),
+            },
+            Fault {
+                ip: 12,
+                kind: FaultKind::IllegalAddress(-1),
+            },
+        ]
+    );
+    let mut program = program.to_vec();
+    program.resize(21, 0);
+    assert_eq!(
+        preflight(&program, Validation::Lenient),
+        [Fault {
+            ip: 12,
+            kind: FaultKind::IllegalAddress(-1),
+        }]
+    );
+    // execution can't get past the first bad instruction
+    assert_eq!(
+        preflight(&program, Validation::Strict),
+        [Fault {
+            ip: 7,
+            kind: FaultKind::IllegalMode(11_104),
+        }]
+    );
+
+    // a runner in strict mode faults on the same instruction
+    let mut runner = Runner::new(&mut program);
+    runner.set_validation(Validation::Strict);
+    runner.try_step().unwrap();
+    assert_eq!(
+        runner.try_step().unwrap_err(),
+        Fault {
+            ip: 7,
+            kind: FaultKind::IllegalMode(11_104),
+        }
+    );
+    runner.set_validation(Validation::Lenient);
+    assert_eq!(runner.try_step(), Ok(crate::Step::Output(5)));
+}
@@ -0,0 +1,561 @@
+//! Translating intcode programs into WebAssembly, so that any wasm runtime can run them.
+//!
+//! [`translate`] writes a module in the text format that imports two functions from `env`:
+//! `input`, of type `[] -> [i64]`, which is called for each input instruction and has to return
+//! the next input (there's no way to block), and `output`, of type `[i64] -> []`. It exports its
+//! `memory`, which holds each intcode word as a little-endian `i64`, and a `run` function that
+//! runs the program until it halts. A fault traps.
+//!
+//! The instructions that can be reached from address 0 (as [`disassemble`] finds them) are
+//! compiled into wasm, split up into blocks wherever a jump could land, with a `br_table` on the
+//! instruction pointer to get from one to the next. A jump anywhere else, or a write over any word
+//! of a compiled instruction, switches to an interpreter loop that is part of every module and
+//! runs the rest of the program from memory.
+
+use crate::decode::{disassemble, Instruction, Mode, Opcode, Param};
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+/// The helper functions and the interpreter loop that every module has. `$load` and `$store` trap
+/// outside of memory, and `$store` sets `$dirty` when it writes over compiled code; `$add` and
+/// `$mul` trap on overflow.
+const RUNTIME: &str = r"  (func $load (param $addr i64) (result i64)
+    (if (i64.ge_u (local.get $addr) (global.get $len)) (then unreachable))
+    (i64.load (i32.wrap_i64 (i64.shl (local.get $addr) (i64.const 3)))))
+  (func $store (param $addr i64) (param $value i64)
+    (if (i64.ge_u (local.get $addr) (global.get $len)) (then unreachable))
+    (i64.store (i32.wrap_i64 (i64.shl (local.get $addr) (i64.const 3))) (local.get $value))
+    (if (i32.load8_u (i32.add (global.get $code) (i32.wrap_i64 (local.get $addr))))
+      (then (global.set $dirty (i32.const 1)))))
+  (func $add (param $a i64) (param $b i64) (result i64) (local $r i64)
+    (local.set $r (i64.add (local.get $a) (local.get $b)))
+    (if (i64.lt_s
+          (i64.and (i64.xor (local.get $a) (local.get $r)) (i64.xor (local.get $b) (local.get $r)))
+          (i64.const 0))
+      (then unreachable))
+    (local.get $r))
+  (func $mul (param $a i64) (param $b i64) (result i64) (local $r i64)
+    (local.set $r (i64.mul (local.get $a) (local.get $b)))
+    (if (i64.ne (local.get $a) (i64.const 0))
+      (then
+        ;; div_s traps on its own for -1 and i64::MIN
+        (if (i64.ne (i64.div_s (local.get $r) (local.get $a)) (local.get $b))
+          (then unreachable))))
+    (local.get $r))
+  (func $param (param $pc i64) (param $word i64) (param $n i64) (param $div i64) (result i64)
+    (local $raw i64) (local $mode i64)
+    (local.set $raw (call $load (i64.add (local.get $pc) (local.get $n))))
+    (local.set $mode (i64.rem_u (i64.div_u (local.get $word) (local.get $div)) (i64.const 10)))
+    (if (i64.eqz (local.get $mode)) (then (return (call $load (local.get $raw)))))
+    (if (i64.eq (local.get $mode) (i64.const 1)) (then (return (local.get $raw))))
+    (if (i64.eq (local.get $mode) (i64.const 2))
+      (then (return (call $load (call $add (global.get $base) (local.get $raw))))))
+    unreachable)
+  (func $dest (param $pc i64) (param $word i64) (param $n i64) (param $div i64) (result i64)
+    (local $raw i64) (local $mode i64)
+    (local.set $raw (call $load (i64.add (local.get $pc) (local.get $n))))
+    (local.set $mode (i64.rem_u (i64.div_u (local.get $word) (local.get $div)) (i64.const 10)))
+    (if (i64.eqz (local.get $mode)) (then (return (local.get $raw))))
+    (if (i64.eq (local.get $mode) (i64.const 2))
+      (then (return (call $add (global.get $base) (local.get $raw)))))
+    unreachable)
+  (func $interpret (param $pc i64) (local $word i64) (local $op i64) (local $a i64) (local $b i64)
+    (loop $step
+      (local.set $word (call $load (local.get $pc)))
+      (local.set $op (i64.rem_s (local.get $word) (i64.const 100)))
+      (if (i64.eq (local.get $op) (i64.const 99)) (then (return)))
+      (if (i32.and (i64.ge_s (local.get $op) (i64.const 1)) (i64.le_s (local.get $op) (i64.const 9)))
+        (then) (else unreachable))
+      (if (i64.eq (local.get $op) (i64.const 3))
+        (then
+          (call $store (call $dest (local.get $pc) (local.get $word) (i64.const 1) (i64.const 100))
+            (call $input))
+          (local.set $pc (i64.add (local.get $pc) (i64.const 2)))
+          (br $step)))
+      (local.set $a (call $param (local.get $pc) (local.get $word) (i64.const 1) (i64.const 100)))
+      (if (i64.eq (local.get $op) (i64.const 4))
+        (then
+          (call $output (local.get $a))
+          (local.set $pc (i64.add (local.get $pc) (i64.const 2)))
+          (br $step)))
+      (if (i64.eq (local.get $op) (i64.const 9))
+        (then
+          (global.set $base (call $add (global.get $base) (local.get $a)))
+          (local.set $pc (i64.add (local.get $pc) (i64.const 2)))
+          (br $step)))
+      (local.set $b (call $param (local.get $pc) (local.get $word) (i64.const 2) (i64.const 1000)))
+      (if (i64.eq (local.get $op) (i64.const 5))
+        (then
+          (local.set $pc (select (local.get $b) (i64.add (local.get $pc) (i64.const 3))
+            (i64.ne (local.get $a) (i64.const 0))))
+          (br $step)))
+      (if (i64.eq (local.get $op) (i64.const 6))
+        (then
+          (local.set $pc (select (local.get $b) (i64.add (local.get $pc) (i64.const 3))
+            (i64.eqz (local.get $a))))
+          (br $step)))
+      (call $store (call $dest (local.get $pc) (local.get $word) (i64.const 3) (i64.const 10000))
+        (if (result i64) (i64.eq (local.get $op) (i64.const 1))
+          (then (call $add (local.get $a) (local.get $b)))
+          (else (if (result i64) (i64.eq (local.get $op) (i64.const 2))
+            (then (call $mul (local.get $a) (local.get $b)))
+            (else (if (result i64) (i64.eq (local.get $op) (i64.const 7))
+              (then (i64.extend_i32_u (i64.lt_s (local.get $a) (local.get $b))))
+              (else (i64.extend_i32_u (i64.eq (local.get $a) (local.get $b))))))))))
+      (local.set $pc (i64.add (local.get $pc) (i64.const 4)))
+      (br $step)))
+";
+
+/// Writes a WebAssembly text module that runs the program in `memory`; see the
+/// [module documentation](self) for what it imports and exports.
+///
+/// All of `memory` is included, so it should be as large as the program needs.
+pub fn translate(memory: &[i64], f: &mut dyn fmt::Write) -> fmt::Result {
+    let instructions = disassemble(memory);
+    let mut code = vec![false; memory.len()];
+    for instruction in &instructions {
+        for word in &mut code[instruction.addr..instruction.next()] {
+            *word = true;
+        }
+    }
+
+    // Blocks start wherever a jump could land: at jump targets, and after jumps for the
+    // instruction a call returns to. Execution falls from one block into the next.
+    let mut leaders = BTreeSet::new();
+    leaders.extend(instructions.first().map(|instruction| instruction.addr));
+    for (i, instruction) in instructions.iter().enumerate() {
+        let (falls_through, target) = instruction.successors(memory.len());
+        leaders.extend(target);
+        let jumps = matches!(
+            instruction.opcode,
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse | Opcode::Halt
+        );
+        if let Some(next) = instructions.get(i + 1) {
+            if jumps || !falls_through || next.addr != instruction.next() {
+                leaders.insert(next.addr);
+            }
+        }
+    }
+    let leaders = instructions
+        .iter()
+        .filter(|instruction| leaders.contains(&instruction.addr))
+        .map(|instruction| instruction.addr)
+        .collect::<Vec<_>>();
+
+    let len = memory.len();
+    let bytes = len * 9;
+    writeln!(f, "(module")?;
+    writeln!(f, "  (import \"env\" \"input\" (func $input (result i64)))")?;
+    writeln!(
+        f,
+        "  (import \"env\" \"output\" (func $output (param i64)))"
+    )?;
+    writeln!(
+        f,
+        "  (memory (export \"memory\") {})",
+        bytes.div_ceil(0x10000).max(1)
+    )?;
+    writeln!(f, "  (global $len i64 (i64.const {}))", len)?;
+    writeln!(f, "  (global $code i32 (i32.const {}))", len * 8)?;
+    writeln!(f, "  (global $base (mut i64) (i64.const 0))")?;
+    writeln!(f, "  (global $dirty (mut i32) (i32.const 0))")?;
+    data(memory, &code, f)?;
+    f.write_str(RUNTIME)?;
+
+    writeln!(
+        f,
+        "  (func (export \"run\") (local $pc i64) (local $a i64) (local $b i64)"
+    )?;
+    writeln!(f, "    (loop $dispatch")?;
+    writeln!(f, "      (block $fallback")?;
+    writeln!(f, "        (br_if $fallback (global.get $dirty))")?;
+    writeln!(
+        f,
+        "        (br_if $fallback (i64.ge_u (local.get $pc) (global.get $len)))"
+    )?;
+    for addr in leaders.iter().rev() {
+        writeln!(f, "        (block $at{}", addr)?;
+    }
+    write!(f, "        (br_table")?;
+    let mut next_leader = leaders.iter().peekable();
+    for addr in 0..leaders.last().map_or(0, |last| last + 1) {
+        if next_leader.peek() == Some(&&addr) {
+            next_leader.next();
+            write!(f, " $at{}", addr)?;
+        } else {
+            write!(f, " $fallback")?;
+        }
+    }
+    writeln!(f, " $fallback (i32.wrap_i64 (local.get $pc)))")?;
+
+    // Within a block the instructions follow on from each other, and only the last can jump.
+    let mut instructions = instructions.iter().peekable();
+    for (i, leader) in leaders.iter().enumerate() {
+        writeln!(f, "        ) ;; {}", leader)?;
+        let end = leaders.get(i + 1).copied();
+        let mut last = None;
+        while let Some(instruction) = instructions.next_if(|next| Some(next.addr) != end) {
+            compile(instruction, &code, f)?;
+            last = Some(instruction);
+        }
+        if let Some(last) = last {
+            if last.successors(len).0 && end != Some(last.next()) {
+                goto(last.next(), f)?;
+            }
+        }
+    }
+    writeln!(f, "      )")?;
+    writeln!(f, "      (call $interpret (local.get $pc))")?;
+    writeln!(f, "    )")?;
+    writeln!(f, "  )")?;
+    writeln!(f, ")")
+}
+
+/// Writes data segments for the non-zero words of memory and for the map of compiled code.
+fn data(memory: &[i64], code: &[bool], f: &mut dyn fmt::Write) -> fmt::Result {
+    const ROW: usize = 8;
+
+    for (row, words) in memory.chunks(ROW).enumerate() {
+        if words.iter().all(|word| *word == 0) {
+            continue;
+        }
+        write!(f, "  (data (i32.const {}) \"", row * ROW * 8)?;
+        for word in words {
+            for byte in &word.to_le_bytes() {
+                write!(f, "\\{:02x}", byte)?;
+            }
+        }
+        writeln!(f, "\")")?;
+    }
+
+    let mut addr = 0;
+    while addr < code.len() {
+        let run = code[addr..].iter().take_while(|word| **word).count();
+        if run > 0 {
+            writeln!(
+                f,
+                "  (data (i32.const {}) \"{}\")",
+                code.len() * 8 + addr,
+                "\\01".repeat(run)
+            )?;
+        }
+        addr += run + 1;
+    }
+    Ok(())
+}
+
+/// Formats a parameter as an expression for its value.
+struct Value(Param);
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.mode {
+            Mode::Position => write!(f, "(call $load (i64.const {}))", self.0.value),
+            Mode::Immediate => write!(f, "(i64.const {})", self.0.value),
+            Mode::Relative => write!(
+                f,
+                "(call $load (call $add (global.get $base) (i64.const {})))",
+                self.0.value
+            ),
+        }
+    }
+}
+
+/// Formats a parameter that is written to as an expression for its address.
+struct Dest(Param);
+
+impl fmt::Display for Dest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.mode {
+            Mode::Relative => write!(
+                f,
+                "(call $add (global.get $base) (i64.const {}))",
+                self.0.value
+            ),
+            _ => write!(f, "(i64.const {})", self.0.value),
+        }
+    }
+}
+
+fn goto(addr: usize, f: &mut dyn fmt::Write) -> fmt::Result {
+    writeln!(
+        f,
+        "        (local.set $pc (i64.const {})) (br $dispatch)",
+        addr
+    )
+}
+
+fn compile(instruction: &Instruction, code: &[bool], f: &mut dyn fmt::Write) -> fmt::Result {
+    let p = instruction.params();
+    writeln!(f, "        ;; {}: {}", instruction.addr, instruction)?;
+    let stored = match instruction.opcode {
+        Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => {
+            let expr = match instruction.opcode {
+                Opcode::Add => "call $add",
+                Opcode::Multiply => "call $mul",
+                Opcode::LessThan => "i64.extend_i32_u (i64.lt_s",
+                _ => "i64.extend_i32_u (i64.eq",
+            };
+            let close = if expr.starts_with("call") { "" } else { ")" };
+            writeln!(
+                f,
+                "        (call $store {} ({} {} {}{}))",
+                Dest(p[2]),
+                expr,
+                Value(p[0]),
+                Value(p[1]),
+                close
+            )?;
+            p[2]
+        }
+        Opcode::Input => {
+            writeln!(f, "        (call $store {} (call $input))", Dest(p[0]))?;
+            p[0]
+        }
+        Opcode::Output => return writeln!(f, "        (call $output {})", Value(p[0])),
+        Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+            writeln!(f, "        (local.set $a {})", Value(p[0]))?;
+            writeln!(f, "        (local.set $b {})", Value(p[1]))?;
+            let test = if instruction.opcode == Opcode::JumpIfTrue {
+                "i64.ne (local.get $a) (i64.const 0)"
+            } else {
+                "i64.eqz (local.get $a)"
+            };
+            return writeln!(
+                f,
+                "        (if ({}) (then (local.set $pc (local.get $b)) (br $dispatch)))",
+                test
+            );
+        }
+        Opcode::AdjustBase => {
+            return writeln!(
+                f,
+                "        (global.set $base (call $add (global.get $base) {}))",
+                Value(p[0])
+            )
+        }
+        Opcode::Halt => return writeln!(f, "        (return)"),
+    };
+
+    // Writing over compiled code leaves the rest of the program to the interpreter. That can't
+    // happen if the address is known and isn't code.
+    let safe = stored.mode == Mode::Position
+        && usize::try_from(stored.value)
+            .ok()
+            .and_then(|addr| code.get(addr))
+            .is_none_or(|code| !code);
+    if !safe {
+        writeln!(
+            f,
+            "        (if (global.get $dirty) (then (local.set $pc (i64.const {})) (br $dispatch)))",
+            instruction.next()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[test]
+fn test_translate() {
+    use crate::{Runner, Step};
+    use alloc::string::String;
+
+    // Checks that parentheses balance outside of strings and comments, and that every label
+    // that's branched to is declared.
+    fn check(text: &str) {
+        let mut depth = 0_i32;
+        let mut labels = BTreeSet::new();
+        for line in text.lines() {
+            let line = line.split(";;").next().unwrap();
+            let mut in_string = false;
+            for c in line.chars() {
+                match c {
+                    '"' => in_string = !in_string,
+                    '(' if !in_string => depth += 1,
+                    ')' if !in_string => depth -= 1,
+                    _ => {}
+                }
+                assert!(depth >= 0, "unbalanced: {}", line);
+            }
+            for declaration in &["(block $", "(loop $"] {
+                if let Some(start) = line.find(declaration) {
+                    labels.insert(String::from(line[start + declaration.len() - 1..].trim()));
+                }
+            }
+        }
+        assert_eq!(depth, 0);
+        for line in text.lines() {
+            let mut words = line.split(|c: char| c.is_whitespace() || c == '(' || c == ')');
+            while let Some(word) = words.next() {
+                if word == "br" || word == "br_if" || word == "br_table" {
+                    for label in words.clone().take_while(|word| word.starts_with('$')) {
+                        assert!(labels.contains(label), "undeclared label {}", label);
+                    }
+                }
+            }
+        }
+    }
+
+    // the multiply-and-call program from the idioms test
+    let mut program = [0; 53];
+    program[..38].copy_from_slice(&[
+        3, 52, 1101, 0, 7, 51, 1, 50, 51, 50, 1001, 52, -1, 52, 1005, 52, 6, 4, 50, 109, 40, 21101,
+        28, 0, 0, 1105, 1, 29, 99, 109, 1, 104, 42, 109, -1, 2105, 1, 0,
+    ]);
+    let mut text = String::new();
+    translate(&program, &mut text).unwrap();
+    check(&text);
+    assert!(text.contains("(import \"env\" \"input\" (func $input (result i64)))"));
+    assert!(text.contains("(func (export \"run\")"));
+    // the data segment holds the program, little-endian
+    assert!(text.contains("(data (i32.const 0) \"\\03\\00\\00\\00\\00\\00\\00\\00\\34\\00"));
+
+    // every instruction a run reaches is compiled, with a block at each jump target and at the
+    // address the call returns to
+    let mut memory = program;
+    let mut runner = Runner::new(&mut memory);
+    runner.input(3);
+    loop {
+        let ip = runner.ip();
+        assert!(
+            text.contains(&alloc::format!(";; {}: ", ip)),
+            "{} not compiled",
+            ip
+        );
+        if runner.step() == Step::Halted {
+            break;
+        }
+    }
+    for leader in &[0, 6, 17, 28, 29] {
+        assert!(text.contains(&alloc::format!("(block $at{}\n", leader)));
+    }
+    // only the relative write of the return address could be to code, so it's the only write
+    // that has to be checked
+    assert_eq!(text.matches("(if (global.get $dirty)").count(), 1);
+
+    // this one overwrites its second output with a halt, so it has to check
+    let mut text = String::new();
+    translate(&[1101, 0, 99, 6, 104, 1, 104, 2, 99], &mut text).unwrap();
+    check(&text);
+    assert!(text.contains(&alloc::format!(
+        "(data (i32.const 72) \"{}\")",
+        "\\01".repeat(9)
+    )));
+    assert!(text
+        .contains("(if (global.get $dirty) (then (local.set $pc (i64.const 4)) (br $dispatch)))"));
+}
+
+#[cfg(test)]
+#[test]
+fn test_execute() {
+    use crate::differential::{generate, lockstep, Engine as _, Reference, Rng};
+    use crate::{Runner, Step};
+    use alloc::collections::VecDeque;
+    use alloc::string::String;
+    use wasmi::core::TrapCode;
+    use wasmi::{Caller, Config, Engine, Linker, Module, Store};
+
+    struct Io {
+        inputs: VecDeque<i64>,
+        outputs: Vec<i64>,
+    }
+
+    // Runs the translation of `program` for at most a million of wasmi's fuel, returning how the
+    // run ended, what it output and the memory it left behind. Asking for more input than there
+    // is fails with an error that isn't a trap.
+    fn execute(program: &[i64], inputs: &[i64]) -> (Result<(), wasmi::Error>, Vec<i64>, Vec<i64>) {
+        let mut text = String::new();
+        translate(program, &mut text).unwrap();
+        let wasm = wat::parse_str(&text).unwrap_or_else(|err| panic!("{}\n{}", err, text));
+        let engine = Engine::new(Config::default().consume_fuel(true));
+        let module = Module::new(&engine, &wasm).unwrap();
+        let io = Io {
+            inputs: inputs.iter().copied().collect(),
+            outputs: Vec::new(),
+        };
+        let mut store = Store::new(&engine, io);
+        store.set_fuel(1_000_000).unwrap();
+        let mut linker = Linker::new(&engine);
+        linker
+            .func_wrap("env", "input", |mut caller: Caller<'_, Io>| {
+                let input = caller.data_mut().inputs.pop_front();
+                input.ok_or_else(|| wasmi::Error::new("out of input"))
+            })
+            .unwrap()
+            .func_wrap("env", "output", |mut caller: Caller<'_, Io>, value: i64| {
+                caller.data_mut().outputs.push(value);
+            })
+            .unwrap();
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .unwrap()
+            .start(&mut store)
+            .unwrap();
+        let run = instance.get_typed_func::<(), ()>(&store, "run").unwrap();
+        let result = run.call(&mut store, ());
+        let memory = instance.get_memory(&store, "memory").unwrap().data(&store)
+            [..program.len() * 8]
+            .chunks_exact(8)
+            .map(|word| i64::from_le_bytes(<[u8; 8]>::try_from(word).unwrap()))
+            .collect();
+        (result, store.into_data().outputs, memory)
+    }
+
+    // the multiply-and-call program from the idioms test, and one that overwrites its second
+    // output with a halt
+    let mut program = [0; 53];
+    program[..38].copy_from_slice(&[
+        3, 52, 1101, 0, 7, 51, 1, 50, 51, 50, 1001, 52, -1, 52, 1005, 52, 6, 4, 50, 109, 40, 21101,
+        28, 0, 0, 1105, 1, 29, 99, 109, 1, 104, 42, 109, -1, 2105, 1, 0,
+    ]);
+    let (result, outputs, _) = execute(&program, &[3]);
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(outputs, [21, 42]);
+    let (result, outputs, memory) = execute(&[1101, 0, 99, 6, 104, 1, 104, 2, 99], &[]);
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!((outputs, memory[6]), (alloc::vec![1], 99));
+
+    // random programs do what the reference engine and the runner do, up to where they run out
+    // of fuel
+    let mut rng = Rng::new(39);
+    let mut ends = [0; 4];
+    for _ in 0..500 {
+        let program = generate(&mut rng, 48);
+        let inputs = (0..rng.below(3))
+            .map(|_| rng.range(-3, 50))
+            .collect::<Vec<_>>();
+        let mut reference = Reference::new(program.clone());
+        let mut memory = program.clone();
+        let mut runner = Runner::new(&mut memory);
+        let summary = lockstep(&mut [&mut reference, &mut runner], &inputs, 500).unwrap();
+        let (result, outputs, memory) = execute(&program, &inputs);
+        let context = || alloc::format!("{:?} with {:?}: {:?}", program, inputs, result);
+        let trap = result.as_ref().err().map(wasmi::Error::as_trap_code);
+        match summary.end {
+            Ok(Step::Halted) => assert_eq!(trap, None, "{}", context()),
+            Ok(Step::Blocked) => assert_eq!(trap, Some(None), "{}", context()),
+            Err(_) => assert!(
+                matches!(trap, Some(Some(code)) if code != TrapCode::OutOfFuel),
+                "{}",
+                context()
+            ),
+            // it might have gone on to output more
+            _ => {
+                assert!(outputs.starts_with(&summary.outputs), "{}", context());
+                ends[2] += 1;
+                continue;
+            }
+        }
+        assert_eq!(outputs, summary.outputs, "{}", context());
+        assert_eq!(memory, reference.memory(), "{}", context());
+        ends[match summary.end {
+            Ok(Step::Halted) => 0,
+            Ok(_) => 1,
+            _ => 3,
+        }] += 1;
+    }
+    assert!(ends.iter().all(|count| *count > 0), "{:?}", ends);
+}
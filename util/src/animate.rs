@@ -0,0 +1,335 @@
+//! Watching a solution work: drawing a frame at a time on the terminal, and recording the frames
+//! as an [asciicast](https://docs.asciinema.org/manual/asciicast/v2/) to play back later.
+//!
+//! The runners animate a day once it's solved, apart from solving it, and only if asked to: set
+//! `AOC_ANIMATE` to draw on the terminal, to a number of frames a second or to nothing for the
+//! default, and `AOC_CAST` to a directory to record each day to, as `day13.cast` and so on.
+
+use crate::Error;
+use serde::Serialize;
+use std::env;
+use std::fmt::{self, Display};
+use std::io::{self, Stdout, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The environment variable that turns on drawing on the terminal.
+pub const ANIMATE_VAR: &str = "AOC_ANIMATE";
+/// The environment variable with the directory to record to.
+pub const CAST_VAR: &str = "AOC_CAST";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// Whether to draw the frames on the terminal.
+    pub terminal: bool,
+    /// How many frames to show a second, or `None` to show them as fast as they come.
+    pub fps: Option<f64>,
+    /// Where to record the frames, if anywhere. This is a file, apart from in the options read
+    /// from the environment, where it's the directory for [`for_day`](Options::for_day) to put
+    /// each day's file in.
+    pub cast: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            terminal: false,
+            fps: Some(30.0),
+            cast: None,
+        }
+    }
+}
+
+impl Options {
+    pub fn from_env() -> Options {
+        let mut options = Options::default();
+        if let Some(value) = env::var_os(ANIMATE_VAR) {
+            options.terminal = true;
+            if let Some(fps) = value.to_str().and_then(|value| value.parse().ok()) {
+                options.fps = Some(fps).filter(|fps| *fps > 0.0);
+            }
+        }
+        options.cast = env::var_os(CAST_VAR).map(PathBuf::from);
+        options
+    }
+
+    /// The options for animating `day`, recording it to its own file in the cast directory.
+    pub fn for_day(&self, day: u32) -> Options {
+        Options {
+            cast: self
+                .cast
+                .as_ref()
+                .map(|dir| dir.join(format!("day{:02}.cast", day))),
+            ..self.clone()
+        }
+    }
+
+    /// Whether there's anywhere for frames to go.
+    pub fn is_enabled(&self) -> bool {
+        self.terminal || self.cast.is_some()
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        self.fps.map(|fps| Duration::from_secs_f64(1.0 / fps))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colour {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Colour {
+    fn code(self) -> u8 {
+        30 + self as u8
+    }
+
+    /// Wraps `s` in the escapes to draw it in this colour.
+    pub fn paint(self, s: impl Display) -> String {
+        format!("\x1b[{}m{}\x1b[0m", self.code(), s)
+    }
+}
+
+/// Colours each character of `text`, usually a rendered grid, with the colour `colour` picks for
+/// it, if any. Runs of the same colour share a single escape.
+pub fn paint(text: &str, mut colour: impl FnMut(char) -> Option<Colour>) -> String {
+    let mut s = String::with_capacity(text.len() * 2);
+    let mut current = None;
+    for c in text.chars() {
+        let next = if c == '\n' { None } else { colour(c) };
+        if next != current {
+            match next {
+                Some(next) => s.push_str(&format!("\x1b[{}m", next.code())),
+                None => s.push_str("\x1b[0m"),
+            }
+            current = next;
+        }
+        s.push(c);
+    }
+    if current.is_some() {
+        s.push_str("\x1b[0m");
+    }
+    s
+}
+
+/// The number of columns `line` takes up on the terminal, leaving out colour escapes.
+fn width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Frames as an asciicast: a header, then each frame with the time it was shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cast {
+    pub width: usize,
+    pub height: usize,
+    pub events: Vec<(Duration, String)>,
+}
+
+#[derive(Serialize)]
+struct Header {
+    version: u32,
+    width: usize,
+    height: usize,
+}
+
+impl Display for Cast {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let header = Header {
+            version: 2,
+            width: self.width,
+            height: self.height,
+        };
+        writeln!(
+            f,
+            "{}",
+            serde_json::to_string(&header).map_err(|_| fmt::Error)?
+        )?;
+        for (time, data) in &self.events {
+            let event = (time.as_secs_f64(), "o", data);
+            writeln!(
+                f,
+                "{}",
+                serde_json::to_string(&event).map_err(|_| fmt::Error)?
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Somewhere to draw frames, as the [`Options`] say.
+pub struct Animation<W: Write = Stdout> {
+    options: Options,
+    out: W,
+    start: Instant,
+    /// When the next frame is due on the terminal.
+    next: Option<Instant>,
+    frames: u32,
+    cast: Cast,
+}
+
+impl Animation {
+    pub fn new(options: Options) -> Animation {
+        Animation::with_output(options, io::stdout())
+    }
+}
+
+impl<W: Write> Animation<W> {
+    pub fn with_output(options: Options, out: W) -> Animation<W> {
+        Animation {
+            options,
+            out,
+            start: Instant::now(),
+            next: None,
+            frames: 0,
+            cast: Cast::default(),
+        }
+    }
+
+    /// Whether frames are going anywhere, so that there's no need to draw them if not.
+    pub fn is_enabled(&self) -> bool {
+        self.options.is_enabled()
+    }
+
+    /// Shows `text` as the next frame, waiting until it's due if the frame rate is limited.
+    pub fn frame(&mut self, text: &str) -> io::Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        // go to the top left and draw over the last frame, clearing what it leaves behind
+        let mut data = String::from(if self.frames == 0 {
+            "\x1b[?25l\x1b[H\x1b[2J"
+        } else {
+            "\x1b[H"
+        });
+        for line in text.lines() {
+            self.cast.width = self.cast.width.max(width(line));
+            data.push_str(line);
+            data.push_str("\x1b[K\r\n");
+        }
+        data.push_str("\x1b[J");
+        self.cast.height = self.cast.height.max(text.lines().count());
+
+        let interval = self.options.interval();
+        if self.options.terminal {
+            if let Some(next) = self.next {
+                let now = Instant::now();
+                if next > now {
+                    thread::sleep(next - now);
+                }
+            }
+            self.next = interval.map(|interval| Instant::now() + interval);
+            self.out.write_all(data.as_bytes())?;
+            self.out.flush()?;
+        }
+        if self.options.cast.is_some() {
+            // recordings play back at the frame rate however long the frames took to work out
+            let time = match interval {
+                Some(interval) => interval * self.frames,
+                None => self.start.elapsed(),
+            };
+            self.cast.events.push((time, data));
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// The frames recorded so far.
+    pub fn cast(&self) -> &Cast {
+        &self.cast
+    }
+
+    /// Puts the terminal's cursor back and saves the recording, if anything was drawn, returning
+    /// the output.
+    pub fn finish(mut self) -> Result<W, Error> {
+        if self.frames == 0 {
+            return Ok(self.out);
+        }
+        if self.options.terminal {
+            self.out
+                .write_all(b"\x1b[?25h")
+                .and_then(|()| self.out.flush())
+                .map_err(|err| Error::io("terminal", err))?;
+        }
+        if let Some(path) = &self.options.cast {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|err| Error::io(dir.display(), err))?;
+            }
+            std::fs::write(path, self.cast.to_string())
+                .map_err(|err| Error::io(path.display(), err))?;
+        }
+        Ok(self.out)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_animate() {
+    assert_eq!(Colour::Red.paint(42), "\x1b[31m42\x1b[0m");
+    let painted = paint("#.\n##", |c| Some(Colour::Green).filter(|_| c == '#'));
+    assert_eq!(painted, "\x1b[32m#\x1b[0m.\n\x1b[32m##\x1b[0m");
+    assert_eq!(width(painted.lines().last().unwrap()), 2);
+
+    let options = Options {
+        terminal: true,
+        fps: None,
+        cast: None,
+    };
+    let mut animation = Animation::with_output(options, Vec::new());
+    animation.frame("ab\ncd").unwrap();
+    animation.frame("e").unwrap();
+    let out = String::from_utf8(animation.finish().unwrap()).unwrap();
+    assert_eq!(
+        out,
+        "\x1b[?25l\x1b[H\x1b[2Jab\x1b[K\r\ncd\x1b[K\r\n\x1b[J\x1b[He\x1b[K\r\n\x1b[J\x1b[?25h"
+    );
+
+    let options = Options {
+        terminal: false,
+        fps: Some(4.0),
+        cast: Some(PathBuf::from("unused.cast")),
+    };
+    let mut animation = Animation::with_output(options, Vec::new());
+    animation
+        .frame(&paint("###", |_| Some(Colour::Blue)))
+        .unwrap();
+    animation.frame("#\n#").unwrap();
+    let cast = animation.cast().to_string();
+    let lines = cast.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], r#"{"version":2,"width":3,"height":2}"#);
+    assert!(lines[1].starts_with(r#"[0.0,"o","\u001b[?25l"#));
+    assert_eq!(
+        lines[2],
+        r#"[0.25,"o","\u001b[H#\u001b[K\r\n#\u001b[K\r\n\u001b[J"]"#
+    );
+
+    let options = Options {
+        cast: Some(PathBuf::from("casts")),
+        ..Options::default()
+    };
+    assert_eq!(
+        options.for_day(7).cast,
+        Some(PathBuf::from("casts/day07.cast"))
+    );
+
+    let mut animation = Animation::with_output(Options::default(), Vec::new());
+    assert!(!animation.is_enabled());
+    animation.frame("nothing").unwrap();
+    assert!(animation.finish().unwrap().is_empty());
+}
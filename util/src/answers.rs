@@ -0,0 +1,225 @@
+use crate::Report;
+use intcode::program::Program;
+use std::fmt::{self, Display};
+
+/// The known-good answers to a day's puzzle for one input.
+///
+/// They're kept in a text file laid out like a [`Report`], without the timings. Answers that
+/// span several lines, like pictures, go on the lines after their heading:
+///
+/// ```text
+/// part 1: 2480
+/// part 2:
+/// xxxx x   xxxx
+/// ```
+///
+/// Either part can be left out if its answer isn't known yet. An intcode program can also give
+/// its answers in its header, as `.answer part1 ...` and `.answer part2 ...`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Answers {
+    pub fn parse(source: &str) -> Result<Answers, String> {
+        let mut parts = [None, None];
+        // the part whose answer is on the lines after its heading
+        let mut picture: Option<usize> = None;
+        for (i, line) in source.lines().enumerate() {
+            let heading = line
+                .strip_prefix("part ")
+                .and_then(|rest| rest.split_once(':'));
+            if let Some((part, value)) = heading {
+                let index = match part {
+                    "1" => 0,
+                    "2" => 1,
+                    _ => return Err(format!("line {}: unknown part {:?}", i + 1, part)),
+                };
+                if parts[index].is_some() {
+                    return Err(format!("line {}: duplicate part {}", i + 1, part));
+                }
+                let value = value.trim();
+                picture = Some(index).filter(|_| value.is_empty());
+                parts[index] = Some(value.to_owned());
+            } else if let Some(index) = picture {
+                let answer = parts[index].get_or_insert_with(String::new);
+                if !answer.is_empty() {
+                    answer.push('\n');
+                }
+                answer.push_str(line.trim_end());
+            } else if !line.trim().is_empty() {
+                return Err(format!("line {}: expected part 1 or part 2", i + 1));
+            }
+        }
+        let [part1, part2] = parts;
+        Ok(Answers {
+            part1: part1.map(|answer| normalize(&answer)),
+            part2: part2.map(|answer| normalize(&answer)),
+        })
+    }
+
+    /// The answers given by the header of the program in `source`, if it is a program with any.
+    pub fn from_program(source: &str) -> Option<Answers> {
+        let program = Program::parse(source).ok()?;
+        let answers = Answers {
+            part1: program.answer("part1").map(normalize),
+            part2: program.answer("part2").map(normalize),
+        };
+        Some(answers).filter(|answers| *answers != Answers::default())
+    }
+
+    /// Fills in the parts these answers leave out from `other`.
+    pub fn or(self, other: Answers) -> Answers {
+        Answers {
+            part1: self.part1.or(other.part1),
+            part2: self.part2.or(other.part2),
+        }
+    }
+
+    /// The answers in a report, to be checked against later.
+    pub fn from_report(report: &Report) -> Answers {
+        Answers {
+            part1: Some(normalize(&report.part1.value)),
+            part2: Some(normalize(&report.part2.value)),
+        }
+    }
+
+    /// Compares the answers in `report` with these, returning the parts that differ.
+    pub fn check(&self, report: &Report) -> Vec<Mismatch> {
+        let parts = [
+            (1, &self.part1, &report.part1.value),
+            (2, &self.part2, &report.part2.value),
+        ];
+        parts
+            .iter()
+            .filter_map(|(part, expected, actual)| {
+                let expected = expected.as_ref()?;
+                let actual = normalize(actual);
+                if *expected == actual {
+                    None
+                } else {
+                    Some(Mismatch {
+                        part: *part,
+                        expected: expected.clone(),
+                        actual,
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+/// Drops trailing whitespace from each line, which editors tend to do to the answers file too.
+fn normalize(value: &str) -> String {
+    let lines = value.trim_end().lines().map(str::trim_end);
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+fn write_part(f: &mut fmt::Formatter, part: u32, value: &str) -> fmt::Result {
+    if value.contains('\n') {
+        writeln!(f, "part {}:\n{}", part, value)
+    } else {
+        writeln!(f, "part {}: {}", part, value)
+    }
+}
+
+impl Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(value) = &self.part1 {
+            write_part(f, 1, value)?;
+        }
+        if let Some(value) = &self.part2 {
+            write_part(f, 2, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// An answer that differs from the known-good one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub part: u32,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.expected.contains('\n') || self.actual.contains('\n') {
+            write!(
+                f,
+                "part {}: expected\n{}\ngot\n{}",
+                self.part, self.expected, self.actual
+            )
+        } else {
+            write!(
+                f,
+                "part {}: expected {}, got {}",
+                self.part, self.expected, self.actual
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_answers() {
+    use crate::Answer;
+    use std::time::Duration;
+
+    let answers = Answers::parse("part 1: 42\npart 2:\n#  # \n ##\n\n").unwrap();
+    assert_eq!(answers.part1.as_deref(), Some("42"));
+    assert_eq!(answers.part2.as_deref(), Some("#  #\n ##"));
+    assert_eq!(Answers::parse(&answers.to_string()), Ok(answers.clone()));
+
+    let answer = |value: &str| Answer {
+        value: value.to_owned(),
+        time: Duration::default(),
+    };
+    let mut report = Report {
+        day: 1,
+        parse: Duration::default(),
+        part1: answer("42"),
+        part2: answer("#  #  \n ##  \n"),
+    };
+    assert!(answers.check(&report).is_empty());
+    assert_eq!(Answers::from_report(&report), answers);
+    report.part1 = answer("43");
+    assert_eq!(
+        answers
+            .check(&report)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        ["part 1: expected 42, got 43"]
+    );
+    // parts without a known answer aren't checked
+    assert!(Answers::parse("part 2:\n#  #\n ##")
+        .unwrap()
+        .check(&report)
+        .is_empty());
+
+    assert_eq!(
+        Answers::parse("part 1: 1\npart 1: 2"),
+        Err(String::from("line 2: duplicate part 1"))
+    );
+    assert_eq!(
+        Answers::parse("part 3: 1"),
+        Err(String::from("line 1: unknown part \"3\""))
+    );
+    assert_eq!(
+        Answers::parse("42"),
+        Err(String::from("line 1: expected part 1 or part 2"))
+    );
+
+    let header = Answers::from_program(".answer part1 42\n.answer part2 7\n99").unwrap();
+    assert_eq!(header.part2.as_deref(), Some("7"));
+    assert_eq!(Answers::from_program("99"), None);
+    assert_eq!(Answers::from_program("#.#\n.#."), None);
+    let file = Answers::parse("part 1: 43").unwrap();
+    assert_eq!(
+        file.or(header),
+        Answers::parse("part 1: 43\npart 2: 7").unwrap()
+    );
+}
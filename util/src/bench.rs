@@ -0,0 +1,257 @@
+//! Timing each day's parse and parts over many runs, and comparing the timings with an earlier
+//! set to find regressions.
+
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::hint;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Runs to do and throw away before timing, to warm up caches and the like.
+    pub warmup: usize,
+    pub runs: usize,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            warmup: 2,
+            runs: 10,
+        }
+    }
+}
+
+/// Timings of one step over several runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    #[serde(with = "nanos")]
+    pub min: Duration,
+    #[serde(with = "nanos")]
+    pub median: Duration,
+    #[serde(with = "nanos")]
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Runs `f` as many times as `options` asks, timing each run after the warmup.
+    pub fn measure<T>(options: &Options, mut f: impl FnMut() -> T) -> Stats {
+        for _ in 0..options.warmup {
+            hint::black_box(f());
+        }
+        let mut samples = (0..options.runs.max(1))
+            .map(|_| {
+                let start = Instant::now();
+                hint::black_box(f());
+                start.elapsed()
+            })
+            .collect::<Vec<_>>();
+        samples.sort();
+        Stats {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Durations are kept as whole nanoseconds in the JSON.
+mod nanos {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::convert::TryFrom;
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        u64::deserialize(d).map(Duration::from_nanos)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayBench {
+    pub day: u32,
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+}
+
+impl DayBench {
+    fn stages(&self) -> [(&'static str, &Stats); 3] {
+        [
+            ("parse", &self.parse),
+            ("part 1", &self.part1),
+            ("part 2", &self.part2),
+        ]
+    }
+}
+
+impl Display for DayBench {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (stage, stats) in &self.stages() {
+            writeln!(
+                f,
+                "day {:<2}  {:<6}  {:>10}  {:>10}  {:>10}",
+                self.day,
+                stage,
+                format!("{:.1?}", stats.min),
+                format!("{:.1?}", stats.median),
+                format!("{:.1?}", stats.max)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The timings for a run of the benchmarks, as saved between runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Results {
+    pub days: Vec<DayBench>,
+}
+
+impl Results {
+    pub fn load(path: &Path) -> Result<Results, Error> {
+        let source = std::fs::read_to_string(path).map_err(|err| Error::io(path.display(), err))?;
+        serde_json::from_str(&source).map_err(|err| Error::Invalid {
+            input: path.display().to_string(),
+            error: err.into(),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self).expect("results are always valid JSON");
+        std::fs::write(path, json + "\n").map_err(|err| Error::io(path.display(), err))
+    }
+
+    /// Adds the days in `other` that aren't in these results, keeping the days in order.
+    pub fn merge(&mut self, other: &Results) {
+        for day in &other.days {
+            if !self.days.iter().any(|this| this.day == day.day) {
+                self.days.push(day.clone());
+            }
+        }
+        self.days.sort_by_key(|day| day.day);
+    }
+
+    /// The steps whose fastest run has changed by more than `threshold` (a fraction, so 0.1 is
+    /// 10%) since `baseline`. The fastest run is the one least disturbed by whatever else the
+    /// machine was doing; steps that take less than 10µs either way are too noisy to compare
+    /// and are left out.
+    pub fn compare(&self, baseline: &Results, threshold: f64) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for day in &self.days {
+            let before = match baseline.days.iter().find(|before| before.day == day.day) {
+                Some(before) => before,
+                None => continue,
+            };
+            for ((stage, after), (_, before)) in day.stages().iter().zip(&before.stages()) {
+                let change = Change {
+                    day: day.day,
+                    stage,
+                    before: before.min,
+                    after: after.min,
+                };
+                let noise = Duration::from_micros(10);
+                if (change.before > noise || change.after > noise)
+                    && (change.ratio() - 1.0).abs() > threshold
+                {
+                    changes.push(change);
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// A step whose fastest run differs from the baseline's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    pub day: u32,
+    pub stage: &'static str,
+    pub before: Duration,
+    pub after: Duration,
+}
+
+impl Change {
+    /// How many times longer the step takes now.
+    pub fn ratio(&self) -> f64 {
+        self.after.as_secs_f64() / self.before.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+
+    pub fn is_regression(&self) -> bool {
+        self.after > self.before
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "day {} {}: {:.1?} -> {:.1?} ({:+.0}%){}",
+            self.day,
+            self.stage,
+            self.before,
+            self.after,
+            (self.ratio() - 1.0) * 100.0,
+            if self.is_regression() {
+                " regression"
+            } else {
+                ""
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_bench() {
+    let mut calls = 0;
+    let options = Options { warmup: 1, runs: 3 };
+    let stats = Stats::measure(&options, || calls += 1);
+    assert_eq!(calls, 4);
+    assert!(stats.min <= stats.median && stats.median <= stats.max);
+
+    let stats = |min| Stats {
+        min: Duration::from_micros(min),
+        median: Duration::from_micros(min * 2),
+        max: Duration::from_micros(min * 4),
+    };
+    let day = |day, parse, part1, part2| DayBench {
+        day,
+        parse: stats(parse),
+        part1: stats(part1),
+        part2: stats(part2),
+    };
+    let baseline = Results {
+        days: vec![day(1, 5, 100, 1000), day(2, 0, 0, 0)],
+    };
+    let results = Results {
+        days: vec![day(1, 10, 150, 800), day(2, 0, 0, 0), day(3, 1, 1, 1)],
+    };
+    let changes = results.compare(&baseline, 0.1);
+    assert_eq!(
+        changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        [
+            "day 1 part 1: 100.0µs -> 150.0µs (+50%) regression",
+            "day 1 part 2: 1.0ms -> 800.0µs (-20%)",
+        ]
+    );
+    assert!(results.compare(&baseline, 0.6).is_empty());
+
+    let mut merged = Results {
+        days: vec![day(3, 1, 1, 1)],
+    };
+    merged.merge(&baseline);
+    assert_eq!(
+        merged.days.iter().map(|day| day.day).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+
+    let json = serde_json::to_string(&results).unwrap();
+    assert!(json.starts_with(r#"{"days":[{"day":1,"parse":{"min":10000,"median":20000,"#));
+    assert_eq!(serde_json::from_str::<Results>(&json).unwrap(), results);
+}
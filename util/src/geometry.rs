@@ -0,0 +1,281 @@
+//! Points on the plane, the vectors between them, and the four directions a robot can face.
+//!
+//! As in [`grid`](crate::grid), `y` grows downwards, so up is `(0, -1)`.
+
+use crate::grid::Coord;
+use gcd::Gcd;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+
+    /// The number of steps from here to `other` moving only up, down, left and right.
+    pub fn manhattan(self, other: Point) -> i64 {
+        (other - self).manhattan()
+    }
+}
+
+impl From<Coord> for Point {
+    fn from((x, y): Coord) -> Point {
+        Point { x, y }
+    }
+}
+
+impl From<Point> for Coord {
+    fn from(point: Point) -> Coord {
+        (point.x, point.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Vector {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Vector {
+    pub const ZERO: Vector = Vector { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Vector {
+        Vector { x, y }
+    }
+
+    pub fn manhattan(self) -> i64 {
+        self.x.abs() + self.y.abs()
+    }
+
+    /// The shortest vector in the same direction with whole-number parts, so that stepping by it
+    /// from a point passes through every point on the way that has whole-number coordinates.
+    pub fn reduced(self) -> Vector {
+        let gcd = self.x.unsigned_abs().gcd(self.y.unsigned_abs()) as i64;
+        if gcd == 0 {
+            self
+        } else {
+            Vector::new(self.x / gcd, self.y / gcd)
+        }
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Vector) -> Point {
+        Point::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, rhs: Vector) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Vector) -> Point {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign<Vector> for Point {
+    fn sub_assign(&mut self, rhs: Vector) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sub for Point {
+    type Output = Vector;
+
+    fn sub(self, rhs: Point) -> Vector {
+        Vector::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, rhs: Vector) -> Vector {
+        Vector::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, rhs: Vector) -> Vector {
+        Vector::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<i64> for Vector {
+    type Output = Vector;
+
+    fn mul(self, rhs: i64) -> Vector {
+        Vector::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Which way to turn, for robots that are steered rather than pointed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// Reads a direction written as `U`/`D`/`L`/`R`, as a compass point `N`/`S`/`W`/`E`, or as
+    /// an arrow `^`/`v`/`<`/`>`.
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            'U' | 'N' | '^' => Some(Direction::Up),
+            'D' | 'S' | 'v' => Some(Direction::Down),
+            'L' | 'W' | '<' => Some(Direction::Left),
+            'R' | 'E' | '>' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    /// Reads a direction as intcode droids take them: 1 for north, 2 for south, 3 for west and 4
+    /// for east.
+    pub fn from_intcode(n: i64) -> Option<Direction> {
+        match n {
+            1 => Some(Direction::Up),
+            2 => Some(Direction::Down),
+            3 => Some(Direction::Left),
+            4 => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    pub fn to_intcode(self) -> i64 {
+        match self {
+            Direction::Up => 1,
+            Direction::Down => 2,
+            Direction::Left => 3,
+            Direction::Right => 4,
+        }
+    }
+
+    /// The vector for a single step this way.
+    pub fn vector(self) -> Vector {
+        match self {
+            Direction::Up => Vector::new(0, -1),
+            Direction::Down => Vector::new(0, 1),
+            Direction::Left => Vector::new(-1, 0),
+            Direction::Right => Vector::new(1, 0),
+        }
+    }
+
+    pub fn left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn right(self) -> Direction {
+        self.left().reverse()
+    }
+
+    pub fn reverse(self) -> Direction {
+        self.left().left()
+    }
+
+    pub fn turn(self, turn: Turn) -> Direction {
+        match turn {
+            Turn::Left => self.left(),
+            Turn::Right => self.right(),
+        }
+    }
+}
+
+impl Turn {
+    /// Reads a turn as intcode robots give them: 0 for left and 1 for right.
+    pub fn from_intcode(n: i64) -> Option<Turn> {
+        match n {
+            0 => Some(Turn::Left),
+            1 => Some(Turn::Right),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_geometry() {
+    let a = Point::new(2, 4);
+    let b = Point::new(-1, 8);
+    assert_eq!(b - a, Vector::new(-3, 4));
+    assert_eq!(a + (b - a), b);
+    assert_eq!(a.manhattan(b), 7);
+    assert_eq!(Point::ORIGIN - Vector::new(1, 1) * 2, Point::new(-2, -2));
+    assert_eq!(Point::from((3, 5)), Point::new(3, 5));
+    assert_eq!(Coord::from(a), (2, 4));
+
+    assert_eq!(Vector::new(2, 2).reduced(), Vector::new(1, 1));
+    assert_eq!(Vector::new(-2, -3).reduced(), Vector::new(-2, -3));
+    assert_eq!(Vector::new(0, -6).reduced(), Vector::new(0, -1));
+    assert_eq!(Vector::ZERO.reduced(), Vector::ZERO);
+
+    let mut pos = Point::ORIGIN;
+    let mut dir = Direction::Up;
+    for turn in [Turn::Right, Turn::Right, Turn::Left] {
+        dir = dir.turn(turn);
+        pos += dir.vector();
+    }
+    assert_eq!((pos, dir), (Point::new(2, 1), Direction::Right));
+    assert_eq!(Direction::Up.reverse(), Direction::Down);
+    assert_eq!(Direction::Left.right(), Direction::Up);
+    for dir in Direction::ALL {
+        assert_eq!(Direction::from_intcode(dir.to_intcode()), Some(dir));
+        assert_eq!(-dir.vector(), dir.reverse().vector());
+    }
+    assert_eq!(
+        "UNDSLWRE"
+            .chars()
+            .map(Direction::from_char)
+            .collect::<Vec<_>>(),
+        "^^vv<<>>"
+            .chars()
+            .map(Direction::from_char)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(Direction::from_char('x'), None);
+    assert_eq!(Turn::from_intcode(2), None);
+}
@@ -0,0 +1,377 @@
+//! Two-dimensional grids: dense ones for maps read from text, and sparse ones for maps drawn a
+//! cell at a time by a robot wandering about.
+//!
+//! Coordinates are `(x, y)` with `y` growing downwards, as the puzzles draw them. They're signed
+//! so that a step off the edge is just a coordinate that isn't in the grid.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+pub type Coord = (i64, i64);
+
+/// The offsets to the cells up, down, left and right of a cell.
+pub const NEIGHBOURS4: [Coord; 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+/// The offsets to the cells around a cell, diagonals included.
+pub const NEIGHBOURS8: [Coord; 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+pub fn neighbours4(coord: Coord) -> impl Iterator<Item = Coord> {
+    NEIGHBOURS4
+        .iter()
+        .map(move |(dx, dy)| (coord.0 + dx, coord.1 + dy))
+}
+
+pub fn neighbours8(coord: Coord) -> impl Iterator<Item = Coord> {
+    NEIGHBOURS8
+        .iter()
+        .map(move |(dx, dy)| (coord.0 + dx, coord.1 + dy))
+}
+
+/// A rectangular grid with a cell at every coordinate from `(0, 0)` up to its width and height.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Parses a grid with a row on each line, turning each character into a cell with `cell`.
+    ///
+    /// Every character of a line is a cell, spaces included, so a rendered grid parses back as it
+    /// was drawn. Empty lines are skipped, and so is the `\r` of a `\r\n` line ending. Every row
+    /// must be the same length.
+    pub fn parse(s: &str, mut cell: impl FnMut(char) -> Option<T>) -> Result<Grid<T>, ParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            let mut row = 0;
+            for (column, c) in line.chars().enumerate() {
+                cells.push(cell(c).ok_or(ParseError {
+                    line: i + 1,
+                    kind: ParseErrorKind::Unexpected {
+                        column: column + 1,
+                        c,
+                    },
+                })?);
+                row += 1;
+            }
+            match width {
+                Some(width) if width != row => {
+                    return Err(ParseError {
+                        line: i + 1,
+                        kind: ParseErrorKind::Ragged {
+                            expected: width,
+                            found: row,
+                        },
+                    })
+                }
+                _ => width = Some(row),
+            }
+            height += 1;
+        }
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index_of(&self, coord: Coord) -> Option<usize> {
+        let x = usize::try_from(coord.0).ok().filter(|x| *x < self.width)?;
+        let y = usize::try_from(coord.1).ok().filter(|y| *y < self.height)?;
+        Some(y * self.width + x)
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        self.index_of(coord).is_some()
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.index_of(coord).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        self.index_of(coord).map(move |i| &mut self.cells[i])
+    }
+
+    /// Every coordinate in the grid, a row at a time.
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.coords().zip(&self.cells)
+    }
+
+    /// The cells up, down, left and right of `coord` that are in the grid.
+    pub fn neighbours4(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        neighbours4(coord).filter(move |coord| self.contains(*coord))
+    }
+
+    /// The cells around `coord`, diagonals included, that are in the grid.
+    pub fn neighbours8(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        neighbours8(coord).filter(move |coord| self.contains(*coord))
+    }
+
+    /// Draws the grid with a row on each line, turning each cell into a character with `cell`.
+    pub fn render(&self, mut cell: impl FnMut(&T) -> char) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width.max(1)) {
+            s.extend(row.iter().map(&mut cell));
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &T {
+        self.get(coord)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", coord))
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut T {
+        self.get_mut(coord)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", coord))
+    }
+}
+
+/// A grid that only has the cells that have been set, anywhere at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sparse<T> {
+    cells: HashMap<Coord, T>,
+}
+
+impl<T> Default for Sparse<T> {
+    fn default() -> Sparse<T> {
+        Sparse {
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Sparse<T> {
+    pub fn new() -> Sparse<T> {
+        Sparse::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.cells.get(&coord)
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        self.cells.get_mut(&coord)
+    }
+
+    pub fn insert(&mut self, coord: Coord, cell: T) -> Option<T> {
+        self.cells.insert(coord, cell)
+    }
+
+    pub fn remove(&mut self, coord: Coord) -> Option<T> {
+        self.cells.remove(&coord)
+    }
+
+    /// The cells that have been set, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.cells.iter().map(|(coord, cell)| (*coord, cell))
+    }
+
+    /// The smallest and largest coordinates of the cells that have been set, if any have.
+    pub fn bounds(&self) -> Option<(Coord, Coord)> {
+        let mut coords = self.cells.keys();
+        let first = *coords.next()?;
+        Some(coords.fold((first, first), |(min, max), &(x, y)| {
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        }))
+    }
+
+    /// Draws the cells within the bounds with a row on each line, turning each cell into a
+    /// character with `cell`, which is given `None` for cells that haven't been set.
+    pub fn render(&self, mut cell: impl FnMut(Coord, Option<&T>) -> char) -> String {
+        let mut s = String::new();
+        if let Some((min, max)) = self.bounds() {
+            for y in min.1..=max.1 {
+                for x in min.0..=max.0 {
+                    s.push(cell((x, y), self.get((x, y))));
+                }
+                s.push('\n');
+            }
+        }
+        s
+    }
+}
+
+impl<T> Index<Coord> for Sparse<T> {
+    type Output = T;
+
+    fn index(&self, coord: Coord) -> &T {
+        self.get(coord)
+            .unwrap_or_else(|| panic!("{:?} hasn't been set", coord))
+    }
+}
+
+impl<T> Extend<(Coord, T)> for Sparse<T> {
+    fn extend<I: IntoIterator<Item = (Coord, T)>>(&mut self, iter: I) {
+        self.cells.extend(iter);
+    }
+}
+
+impl<T> std::iter::FromIterator<(Coord, T)> for Sparse<T> {
+    fn from_iter<I: IntoIterator<Item = (Coord, T)>>(iter: I) -> Sparse<T> {
+        Sparse {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the error is on, counting from 1.
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A character that isn't a cell, at a column counting from 1.
+    Unexpected { column: usize, c: char },
+    /// A row that isn't as long as the first.
+    Ragged { expected: usize, found: usize },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::Unexpected { column, c } => {
+                write!(f, "{}:{}: unexpected {:?}", self.line, column, c)
+            }
+            ParseErrorKind::Ragged { expected, found } => write!(
+                f,
+                "{}: row is {} cells long, expected {}",
+                self.line, found, expected
+            ),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[cfg(test)]
+#[test]
+fn test_grid() {
+    let wall = |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    };
+    let mut grid = Grid::parse(
+        "#..\n\
+         .#.\n\
+        ",
+        wall,
+    )
+    .unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert!(grid[(0, 0)] && grid[(1, 1)] && !grid[(2, 1)]);
+    assert_eq!(grid.get((3, 0)), None);
+    assert_eq!(grid.get((-1, 0)), None);
+    assert_eq!(
+        grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+        [(0, 1), (1, 0)]
+    );
+    assert_eq!(grid.neighbours8((2, 1)).count(), 3);
+    assert_eq!(grid.iter().filter(|(_, wall)| **wall).count(), 2);
+    grid[(2, 1)] = true;
+    assert_eq!(
+        grid.render(|wall| if *wall { '#' } else { '.' }),
+        "#..\n.##\n"
+    );
+
+    assert_eq!(
+        Grid::parse("#.\n#x", wall).unwrap_err().to_string(),
+        "2:2: unexpected 'x'"
+    );
+    assert_eq!(
+        Grid::parse("#.\n#", wall).unwrap_err().to_string(),
+        "2: row is 1 cells long, expected 2"
+    );
+    assert_eq!(
+        Grid::parse("#.\n  #x", wall).unwrap_err().to_string(),
+        "2:1: unexpected ' '"
+    );
+
+    // spaces are cells like any other, even at either end of a row
+    let tiles = Grid::parse(" # \r\n\n## \r\n", Some).unwrap();
+    assert_eq!((tiles.width(), tiles.height()), (3, 2));
+    assert_eq!(
+        (tiles[(0, 0)], tiles[(1, 0)], tiles[(2, 1)]),
+        (' ', '#', ' ')
+    );
+    assert_eq!(Grid::parse(&tiles.render(|c| *c), Some), Ok(tiles));
+
+    let mut sparse = Sparse::new();
+    assert_eq!(sparse.bounds(), None);
+    assert_eq!(sparse.render(|_, _| '#'), "");
+    sparse.insert((-1, 2), 'a');
+    sparse.insert((1, 0), 'b');
+    assert_eq!(sparse.bounds(), Some(((-1, 0), (1, 2))));
+    assert_eq!(
+        sparse.render(|_, cell| cell.copied().unwrap_or('.')),
+        "..b\n...\na..\n"
+    );
+    assert_eq!(
+        neighbours8((0, 0))
+            .filter(|c| sparse.get(*c).is_some())
+            .count(),
+        1
+    );
+}
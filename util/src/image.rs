@@ -0,0 +1,371 @@
+//! Saving grids as pictures, for the days whose answers are pictures, in the Netpbm formats
+//! (`.pbm`, `.pgm` and `.ppm`) or as PNG.
+//!
+//! When `AOC_IMAGE` names a directory, the runners save the picture each day draws there once the
+//! day is solved: day 8's as `day08.png`, or in whichever of the other formats `AOC_IMAGE_FORMAT`
+//! names. Cells are drawn as squares `AOC_IMAGE_SCALE` pixels wide, so the pictures can be read.
+
+use crate::grid::{Grid, Sparse};
+use crate::Error;
+use std::convert::TryFrom;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// The environment variable with the directory to save pictures in.
+pub const IMAGE_VAR: &str = "AOC_IMAGE";
+/// The environment variable with the format to save pictures in, as its file extension.
+pub const FORMAT_VAR: &str = "AOC_IMAGE_FORMAT";
+/// The environment variable with how far to scale a picture up.
+pub const SCALE_VAR: &str = "AOC_IMAGE_SCALE";
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+/// Colours for cells that are small numbers, like the puzzles' pixels and panels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colours: Vec<Rgb>,
+    /// The colour for numbers the palette has no colour for.
+    pub fallback: Rgb,
+}
+
+impl Palette {
+    /// A palette giving `colours[n]` to the number `n`.
+    pub fn new(colours: &[Rgb]) -> Palette {
+        Palette {
+            colours: colours.to_vec(),
+            fallback: [128, 128, 128],
+        }
+    }
+
+    /// Black for 0 and white for 1, as the puzzles' pictures are drawn.
+    pub fn mono() -> Palette {
+        Palette::new(&[BLACK, WHITE])
+    }
+
+    pub fn colour(&self, n: i64) -> Rgb {
+        usize::try_from(n)
+            .ok()
+            .and_then(|n| self.colours.get(n))
+            .copied()
+            .unwrap_or(self.fallback)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    /// A pixel for each cell of `grid`, coloured by `colour`.
+    pub fn from_grid<T>(grid: &Grid<T>, colour: impl FnMut(&T) -> Rgb) -> Image {
+        Image {
+            width: grid.width(),
+            height: grid.height(),
+            pixels: grid.iter().map(|(_, cell)| cell).map(colour).collect(),
+        }
+    }
+
+    /// A pixel for each cell within the bounds of `sparse`, coloured by `colour`, which is given
+    /// `None` for cells that haven't been set.
+    pub fn from_sparse<T>(sparse: &Sparse<T>, mut colour: impl FnMut(Option<&T>) -> Rgb) -> Image {
+        let ((x_min, y_min), (x_max, y_max)) = match sparse.bounds() {
+            Some(bounds) => bounds,
+            None => return Image::new(0, 0, BLACK),
+        };
+        let mut pixels = Vec::new();
+        for y in y_min..=y_max {
+            for x in x_min..=x_max {
+                pixels.push(colour(sparse.get((x, y))));
+            }
+        }
+        Image {
+            width: (x_max - x_min + 1) as usize,
+            height: (y_max - y_min + 1) as usize,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[Rgb]> {
+        self.pixels.chunks(self.width.max(1))
+    }
+
+    /// The image with each pixel drawn as a `factor` by `factor` square.
+    pub fn scale(&self, factor: usize) -> Image {
+        let mut pixels = Vec::with_capacity(self.pixels.len() * factor * factor);
+        for row in self.rows() {
+            let row = row
+                .iter()
+                .flat_map(|pixel| std::iter::repeat_n(*pixel, factor))
+                .collect::<Vec<_>>();
+            for _ in 0..factor {
+                pixels.extend_from_slice(&row);
+            }
+        }
+        Image {
+            width: self.width * factor,
+            height: self.height * factor,
+            pixels,
+        }
+    }
+
+    /// The image as a black and white PBM, with dark pixels black and light ones white.
+    pub fn pbm(&self) -> Vec<u8> {
+        let mut data = format!("P4\n{} {}\n", self.width, self.height).into_bytes();
+        for row in self.rows() {
+            // each row is packed eight pixels to a byte, with a set bit for black
+            for pixels in row.chunks(8) {
+                let byte = pixels.iter().enumerate().fold(0, |byte, (i, pixel)| {
+                    byte | (u8::from(luminance(*pixel) < 128) << (7 - i))
+                });
+                data.push(byte);
+            }
+        }
+        data
+    }
+
+    /// The image as a greyscale PGM.
+    pub fn pgm(&self) -> Vec<u8> {
+        let mut data = format!("P5\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.extend(self.pixels.iter().map(|pixel| luminance(*pixel)));
+        data
+    }
+
+    /// The image as a colour PPM.
+    pub fn ppm(&self) -> Vec<u8> {
+        let mut data = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.extend(self.pixels.iter().flatten());
+        data
+    }
+
+    /// The image as a colour PNG.
+    pub fn png(&self) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut header = Vec::new();
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // 8 bits a channel, RGB, and the only compression, filtering and interlacing there are
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        chunk(&mut data, b"IHDR", &header);
+        // each row starts with the filter it uses, and the rows here aren't filtered
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.rows().take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+        chunk(&mut data, b"IDAT", &zlib(&raw));
+        chunk(&mut data, b"IEND", &[]);
+        data
+    }
+
+    /// Saves the image to `path`, in the format its extension names.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let data = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("pbm") => self.pbm(),
+            Some("pgm") => self.pgm(),
+            Some("ppm") => self.ppm(),
+            Some("png") => self.png(),
+            _ => {
+                return Err(Error::Invalid {
+                    input: path.display().to_string(),
+                    error: "expected a .pbm, .pgm, .ppm or .png file".into(),
+                })
+            }
+        };
+        std::fs::write(path, data).map_err(|err| Error::io(path.display(), err))
+    }
+}
+
+fn luminance([r, g, b]: Rgb) -> u8 {
+    ((u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114) / 1000) as u8
+}
+
+/// Adds a PNG chunk to `png`: its length, type, data and checksum.
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, byte| {
+        (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1, 0), |(a, b), byte| {
+        let a = (a + u32::from(*byte)) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+/// Wraps `data` in a zlib stream without compressing it, which keeps the encoder small and is
+/// plenty for pictures this size.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        stream.push(u8::from(blocks.peek().is_none()));
+        let len = block.len() as u16;
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The directory to save pictures in, if anywhere.
+    pub dir: Option<PathBuf>,
+    /// The extension of the format to save pictures in: `pbm`, `pgm`, `ppm` or `png`.
+    pub format: String,
+    /// How many pixels wide and high to draw each cell.
+    pub scale: usize,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            dir: None,
+            format: String::from("png"),
+            scale: 8,
+        }
+    }
+}
+
+impl Options {
+    pub fn from_env() -> Options {
+        let mut options = Options {
+            dir: env::var_os(IMAGE_VAR).map(PathBuf::from),
+            ..Options::default()
+        };
+        if let Ok(format) = env::var(FORMAT_VAR) {
+            options.format = format;
+        }
+        if let Some(scale) = env::var(SCALE_VAR)
+            .ok()
+            .and_then(|scale| scale.parse().ok())
+        {
+            options.scale = scale;
+        }
+        options
+    }
+
+    /// Whether there's anywhere to save pictures.
+    pub fn is_enabled(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// Where to save `day`'s picture, if anywhere.
+    pub fn path(&self, day: u32) -> Option<PathBuf> {
+        let name = format!("day{:02}.{}", day, self.format);
+        self.dir.as_ref().map(|dir| dir.join(name))
+    }
+
+    /// Scales up and saves `image` as `day`'s picture, if there's somewhere to save it.
+    pub fn save(&self, day: u32, image: &Image) -> Result<(), Error> {
+        let path = match self.path(day) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| Error::io(dir.display(), err))?;
+        }
+        image.scale(self.scale.max(1)).save(&path)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_image() {
+    let grid = Grid::parse("#.#\n.#.", |c| Some(i64::from(c == '#'))).unwrap();
+    let palette = Palette::new(&[WHITE, [255, 0, 0]]);
+    let image = Image::from_grid(&grid, |cell| palette.colour(*cell));
+    assert_eq!((image.width(), image.height()), (3, 2));
+    assert_eq!(image.get(0, 0), Some([255, 0, 0]));
+    assert_eq!(image.get(1, 0), Some(WHITE));
+    assert_eq!(image.get(3, 0), None);
+    assert_eq!(palette.colour(7), [128, 128, 128]);
+    assert_eq!(Palette::mono().colour(1), WHITE);
+
+    let scaled = image.scale(2);
+    assert_eq!((scaled.width(), scaled.height()), (6, 4));
+    assert_eq!(scaled.get(1, 1), Some([255, 0, 0]));
+    assert_eq!(scaled.get(2, 1), Some(WHITE));
+
+    let sparse = [((-1, 0), 1), ((1, 1), 0)].iter().copied().collect();
+    let image = Image::from_sparse(&sparse, |cell| palette.colour(*cell.unwrap_or(&0)));
+    assert_eq!((image.width(), image.height()), (3, 2));
+    assert_eq!(image.get(0, 0), Some([255, 0, 0]));
+
+    // red is dark enough to be black in a PBM
+    let image = Image::from_grid(&grid, |cell| palette.colour(*cell));
+    assert_eq!(image.pbm(), b"P4\n3 2\n\xa0\x40");
+    assert_eq!(image.pgm(), b"P5\n3 2\n255\n\x4c\xff\x4c\xff\x4c\xff");
+    assert_eq!(&image.ppm()[..14], b"P6\n3 2\n255\n\xff\x00\x00");
+
+    assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    let png = image.png();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(&png[16..29], b"\0\0\0\x03\0\0\0\x02\x08\x02\0\0\0");
+    assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+    // a stored block holding both rows, each a filter byte and three pixels
+    let idat = &png[37..];
+    assert_eq!(&idat[..4], b"IDAT");
+    assert_eq!(&idat[4..11], b"\x78\x01\x01\x14\x00\xeb\xff");
+
+    let options = Options {
+        dir: Some(PathBuf::from("pictures")),
+        format: String::from("pbm"),
+        ..Options::default()
+    };
+    assert_eq!(options.path(8), Some(PathBuf::from("pictures/day08.pbm")));
+    assert_eq!(Options::default().path(8), None);
+}
@@ -1,16 +1,401 @@
+//! Loading puzzle inputs.
+//!
+//! Each day reads its input from the first command-line argument, or from the `AOC_INPUT`
+//! environment variable if there are no arguments, or from `input.txt` in the crate's directory
+//! if neither is set. An input is one of:
+//!
+//! - `-`, to read from stdin;
+//! - `@NAME`, for the named input `inputs/NAME.txt` in the crate's directory, so that a day can
+//!   keep more than one person's puzzle input;
+//! - anything else, which is a path to read.
+//!
+//! Each day's answers come from its [`Solution`], which [`main`] runs on that input. The
+//! known-good [`Answers`] for an input are kept next to it, with the extension `.answers`
+//! (`input.answers` or `inputs/NAME.answers`), or in the header of an intcode program, and are
+//! checked whenever the day is run.
+
+pub mod animate;
+mod answers;
+pub mod bench;
+pub mod geometry;
+pub mod grid;
+pub mod image;
+pub mod parse;
+pub mod search;
+mod solution;
+
+pub use crate::answers::{Answers, Mismatch};
+pub use crate::solution::{Answer, Day, Report, Solution};
+
+use intcode::program::{ParseError, Program};
+use intcode::session::Session;
 use std::env;
-use std::path::PathBuf;
+use std::error;
+use std::fmt::{self, Display};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// The environment variable an input can be given in.
+pub const INPUT_VAR: &str = "AOC_INPUT";
+
+/// The crate's directory, or the current directory when not run by cargo.
+fn crate_dir() -> PathBuf {
+    env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+/// The path of a file in the crate's directory.
+fn path(name: &str) -> PathBuf {
+    crate_dir().join(name)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    /// `input.txt` in the crate's directory.
+    Default,
+    Stdin,
+    Path(PathBuf),
+    /// `inputs/NAME.txt` in the crate's directory.
+    Named(String),
+}
+
+impl Input {
+    pub fn parse(arg: &str) -> Input {
+        if arg == "-" {
+            Input::Stdin
+        } else if let Some(name) = arg.strip_prefix('@') {
+            Input::Named(name.to_owned())
+        } else {
+            Input::Path(PathBuf::from(arg))
+        }
+    }
+
+    /// The input given on the command line or in [`INPUT_VAR`], or the default.
+    pub fn from_env() -> Input {
+        env::args()
+            .nth(1)
+            .or_else(|| env::var(INPUT_VAR).ok().filter(|var| !var.is_empty()))
+            .map_or(Input::Default, |arg| Input::parse(&arg))
+    }
+
+    /// Every named input the crate has, in name order.
+    pub fn named() -> Result<Vec<Input>, Error> {
+        let dir = path("inputs");
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(Error::io(dir.display(), err)),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|err| Error::io(dir.display(), err))?.path();
+            if path.extension().is_some_and(|ext| ext == "txt") {
+                if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+        names.sort();
+        Ok(names.into_iter().map(Input::Named).collect())
+    }
+
+    fn path(&self, dir: &Path) -> Option<PathBuf> {
+        match self {
+            Input::Default => Some(dir.join("input.txt")),
+            Input::Stdin => None,
+            Input::Path(path) => Some(path.clone()),
+            Input::Named(name) => Some(dir.join("inputs").join(format!("{}.txt", name))),
+        }
+    }
+
+    pub fn read(&self) -> Result<String, Error> {
+        self.read_in(&crate_dir())
+    }
+
+    /// Reads the input, finding the default and named inputs in `dir` rather than the crate's
+    /// directory.
+    pub fn read_in(&self, dir: &Path) -> Result<String, Error> {
+        let result = match self.path(dir) {
+            Some(path) => std::fs::read_to_string(path),
+            None => {
+                let mut source = String::new();
+                io::stdin().read_to_string(&mut source).map(|_| source)
+            }
+        };
+        result.map_err(|err| Error::io(self, err))
+    }
+
+    /// Where the answers for the input are kept, if it's a file.
+    pub fn answers_path(&self, dir: &Path) -> Option<PathBuf> {
+        self.path(dir).map(|path| path.with_extension("answers"))
+    }
+
+    /// Reads the known-good answers for the input, if there are any.
+    pub fn answers_in(&self, dir: &Path) -> Result<Option<Answers>, Error> {
+        let path = match self.answers_path(dir) {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::io(path.display(), err)),
+        };
+        Answers::parse(&source)
+            .map(Some)
+            .map_err(|err| Error::Invalid {
+                input: path.display().to_string(),
+                error: err.into(),
+            })
+    }
+
+    /// Saves `answers` as the known-good answers for the input.
+    pub fn save_answers_in(&self, dir: &Path, answers: &Answers) -> Result<(), Error> {
+        let path = self
+            .answers_path(dir)
+            .ok_or_else(|| Error::io(self, io::ErrorKind::Unsupported.into()))?;
+        std::fs::write(&path, answers.to_string()).map_err(|err| Error::io(path.display(), err))
+    }
+
+    /// The known-good answers for `source`, which was read from the input: those in its answers
+    /// file, with any parts the file leaves out taken from the header of a program.
+    pub fn known_answers_in(&self, dir: &Path, source: &str) -> Result<Option<Answers>, Error> {
+        Ok(
+            match (self.answers_in(dir)?, Answers::from_program(source)) {
+                (Some(answers), Some(header)) => Some(answers.or(header)),
+                (answers, header) => answers.or(header),
+            },
+        )
+    }
+
+    pub fn program(&self) -> Result<Program, Error> {
+        Program::parse(&self.read()?).map_err(|err| Error::Parse {
+            input: self.to_string(),
+            error: err,
+        })
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Input::Default => write!(f, "input.txt"),
+            Input::Stdin => write!(f, "<stdin>"),
+            Input::Path(path) => write!(f, "{}", path.display()),
+            Input::Named(name) => write!(f, "inputs/{}.txt", name),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        input: String,
+        error: io::Error,
+    },
+    Parse {
+        input: String,
+        error: ParseError,
+    },
+    /// The input was read, but a [`Solution`] couldn't parse it.
+    Invalid {
+        input: String,
+        error: Box<dyn error::Error>,
+    },
+}
+
+impl Error {
+    fn io(input: impl Display, error: io::Error) -> Error {
+        Error::Io {
+            input: input.to_string(),
+            error,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { input, error } => write!(f, "{}: {}", input, error),
+            Error::Parse { input, error } => write!(f, "{}:{}", input, error),
+            Error::Invalid { input, error } => write!(f, "{}: {}", input, error),
+        }
+    }
+}
 
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { error, .. } => Some(error),
+            Error::Parse { error, .. } => Some(error),
+            Error::Invalid { error, .. } => Some(error.as_ref()),
+        }
+    }
+}
+
+/// Unwraps a result, or reports the error and exits.
+fn or_exit<T>(result: Result<T, Error>) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    })
+}
+
+/// Reads the input chosen as described in the [crate documentation](crate), exiting with a
+/// message if it can't be read.
 pub fn read_input() -> String {
-    std::fs::read_to_string(
-        PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap()).join("input.txt"),
-    )
-    .unwrap()
+    or_exit(Input::from_env().read())
 }
 
+pub fn read_program() -> Program {
+    or_exit(Input::from_env().program())
+}
+
+/// Reads the program input, with any patches in its header applied.
 pub fn read_intcode() -> Vec<i64> {
-    read_input()
-        .split(',')
-        .map(|s| s.trim().parse().unwrap())
-        .collect()
+    read_program().image()
+}
+
+/// Solves `day` for `source`, which was read from `input`.
+pub fn run(day: &dyn Day, input: &Input, source: &str) -> Result<Report, Error> {
+    day.solve(source).map_err(|error| Error::Invalid {
+        input: input.to_string(),
+        error,
+    })
+}
+
+/// Shows `day` at work on `source`, which was read from `input`, and saves its picture, as
+/// `animation` and `pictures` say.
+pub fn visualise(
+    day: &dyn Day,
+    input: &Input,
+    source: &str,
+    animation: &animate::Options,
+    pictures: &image::Options,
+) -> Result<(), Error> {
+    let invalid = |error| Error::Invalid {
+        input: input.to_string(),
+        error,
+    };
+    if animation.is_enabled() {
+        day.animate(source, animation).map_err(invalid)?;
+    }
+    if pictures.is_enabled() {
+        if let Some(picture) = day.picture(source).map_err(invalid)? {
+            pictures.save(day.day(), &picture)?;
+        }
+    }
+    Ok(())
+}
+
+/// Solves `day` for the input chosen as described in the [crate documentation](crate), and
+/// prints the answers, exiting with a message if they aren't the known-good ones. The day is
+/// [animated](animate) and its [picture](image) saved if the environment asks for them.
+pub fn main(day: &dyn Day) {
+    let input = Input::from_env();
+    let source = or_exit(input.read());
+    let report = or_exit(run(day, &input, &source));
+    let (animation, pictures) = (animate::Options::from_env(), image::Options::from_env());
+    or_exit(visualise(day, &input, &source, &animation, &pictures));
+    print!("{}", report);
+    if let Some(answers) = or_exit(input.known_answers_in(&crate_dir(), &source)) {
+        let mismatches = answers.check(&report);
+        for mismatch in &mismatches {
+            eprintln!("{}", mismatch);
+        }
+        if !mismatches.is_empty() {
+            process::exit(1);
+        }
+    }
+}
+
+/// Reads a session recorded with [`write_session`].
+pub fn read_session(name: &str) -> Result<Session, Error> {
+    let input = Input::Path(path(name));
+    Session::parse(&input.read()?).map_err(|err| Error::Invalid {
+        input: input.to_string(),
+        error: Box::new(err),
+    })
+}
+
+/// Writes a recorded session to a file in the crate's directory, so that it can be replayed.
+pub fn write_session(name: &str, session: &Session) -> Result<(), Error> {
+    std::fs::write(path(name), session.to_string()).map_err(|err| Error::io(name, err))
+}
+
+#[cfg(test)]
+#[test]
+fn test_input() {
+    assert_eq!(Input::parse("-"), Input::Stdin);
+    assert_eq!(Input::parse("@alice"), Input::Named("alice".to_owned()));
+    assert_eq!(
+        Input::parse("../day02/input.txt"),
+        Input::Path(PathBuf::from("../day02/input.txt"))
+    );
+    assert_eq!(Input::Named("bob".to_owned()).to_string(), "inputs/bob.txt");
+
+    assert!(Input::Path(path("Cargo.toml"))
+        .read()
+        .unwrap()
+        .contains("util"));
+    let err = Input::Named("nobody".to_owned()).read().unwrap_err();
+    assert!(err.to_string().starts_with("inputs/nobody.txt: "));
+    assert!(Input::named().unwrap().is_empty());
+    let err = Input::Path(path("src/lib.rs")).program().unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with(&format!("{}:1:", path("src/lib.rs").display())));
+}
+
+#[cfg(test)]
+#[test]
+fn test_visualise() {
+    use crate::grid::Grid;
+    use crate::image::{Image, Palette};
+
+    // a day whose answer is a picture of its input
+    struct Picture;
+
+    impl Solution for Picture {
+        const DAY: u32 = 8;
+
+        type Input = Grid<i64>;
+        type Part1 = usize;
+        type Part2 = String;
+
+        fn parse(source: &str) -> Result<Grid<i64>, Box<dyn error::Error>> {
+            Ok(Grid::parse(source, |c| c.to_digit(2).map(i64::from))?)
+        }
+
+        fn part1(grid: &Grid<i64>) -> usize {
+            grid.iter().filter(|(_, cell)| **cell == 1).count()
+        }
+
+        fn part2(grid: &Grid<i64>) -> String {
+            grid.render(|cell| if *cell == 1 { '#' } else { ' ' })
+        }
+
+        fn picture(grid: &Grid<i64>) -> Option<Image> {
+            let palette = Palette::mono();
+            Some(Image::from_grid(grid, |cell| palette.colour(*cell)))
+        }
+    }
+
+    // stdin can only be read once, so the answers and the picture come from the same source
+    let source = "101\n010\n";
+    let report = run(&Picture, &Input::Stdin, source).unwrap();
+    assert_eq!(report.part1.value, "3");
+    let dir = env::temp_dir().join(format!("util-test-visualise-{}", process::id()));
+    let pictures = image::Options {
+        dir: Some(dir.clone()),
+        format: "pbm".to_owned(),
+        scale: 1,
+    };
+    let animation = animate::Options::default();
+    visualise(&Picture, &Input::Stdin, source, &animation, &pictures).unwrap();
+    let saved = std::fs::read(dir.join("day08.pbm"));
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(saved.unwrap(), b"P4\n3 2\n\x40\xa0");
 }
@@ -0,0 +1,258 @@
+//! Parsing puzzle inputs a piece at a time, with errors that say where the input went wrong.
+//!
+//! A [`Cursor`] walks through a line, and each of its methods reads one thing from the front of
+//! what's left: a number, a name, some punctuation. [`lines`] runs a parser over each line of an
+//! input and [`whole`] runs one over all of it, both checking that nothing is left over.
+
+use std::error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+pub struct Cursor<'a> {
+    rest: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(source: &'a str) -> Cursor<'a> {
+        Cursor {
+            rest: source,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// What's left to parse.
+    pub fn rest(&self) -> &'a str {
+        self.rest
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    /// An error at the cursor, saying what was expected there and what was found instead.
+    pub fn error(&self, expected: impl Display) -> ParseError {
+        let found = match self.peek() {
+            Some('\n') | None => String::from("the end of the line"),
+            Some(c) => format!("{:?}", c),
+        };
+        self.error_msg(format!("expected {}, found {}", expected, found))
+    }
+
+    fn error_msg(&self, message: String) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message,
+        }
+    }
+
+    /// Moves past the first `len` bytes, returning them.
+    fn advance(&mut self, len: usize) -> &'a str {
+        let (taken, rest) = self.rest.split_at(len);
+        for c in taken.chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.rest = rest;
+        taken
+    }
+
+    /// Moves past the longest run of characters that `f` accepts, returning them.
+    fn take_while(&mut self, mut f: impl FnMut(char) -> bool) -> &'a str {
+        let len = self.rest.find(|c| !f(c)).unwrap_or(self.rest.len());
+        self.advance(len)
+    }
+
+    /// Skips spaces and tabs, but not newlines.
+    pub fn spaces(&mut self) {
+        self.take_while(|c| c == ' ' || c == '\t');
+    }
+
+    /// Reads exactly `s`.
+    pub fn literal(&mut self, s: &str) -> Result<(), ParseError> {
+        if self.rest.starts_with(s) {
+            self.advance(s.len());
+            Ok(())
+        } else {
+            Err(self.error(format!("{:?}", s)))
+        }
+    }
+
+    /// Reads a whole number, which may have a sign.
+    pub fn int<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.clone();
+        let sign = if self.rest.starts_with(['-', '+']) {
+            1
+        } else {
+            0
+        };
+        let digits = self.rest[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len() - sign);
+        if digits == 0 {
+            return Err(self.error("a number"));
+        }
+        let number = self.advance(sign + digits);
+        number
+            .parse()
+            .map_err(|_| start.error_msg(format!("{} is out of range", number)))
+    }
+
+    /// Reads a name made of letters, digits and underscores.
+    pub fn ident(&mut self) -> Result<&'a str, ParseError> {
+        match self.take_while(|c| c.is_alphanumeric() || c == '_') {
+            "" => Err(self.error("a name")),
+            ident => Ok(ident),
+        }
+    }
+
+    /// Reads a single character that `f` turns into something, or fails saying that `expected`
+    /// was expected if it doesn't.
+    pub fn char<T>(
+        &mut self,
+        expected: impl Display,
+        f: impl FnOnce(char) -> Option<T>,
+    ) -> Result<T, ParseError> {
+        match self.peek().and_then(f) {
+            Some(value) => {
+                self.advance(self.peek().map_or(0, char::len_utf8));
+                Ok(value)
+            }
+            None => Err(self.error(expected)),
+        }
+    }
+
+    /// Reads one or more things with `item`, each separated from the next by `sep`.
+    pub fn sep_by<T>(
+        &mut self,
+        sep: &str,
+        mut item: impl FnMut(&mut Cursor<'a>) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![item(self)?];
+        while self.rest.starts_with(sep) {
+            self.advance(sep.len());
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Checks that everything has been read.
+    pub fn end(&self) -> Result<(), ParseError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("the end of the line"))
+        }
+    }
+}
+
+/// Parses each line of `source` with `line`, which must read the whole line. Blank lines are
+/// skipped, and so is whitespace at either end of a line, so inputs can be written indented in
+/// string literals.
+pub fn lines<'a, T>(
+    source: &'a str,
+    mut line: impl FnMut(&mut Cursor<'a>) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    let mut items = Vec::new();
+    for (i, text) in source.lines().enumerate() {
+        let trimmed = text.trim_start();
+        if trimmed.trim_end().is_empty() {
+            continue;
+        }
+        let mut cursor = Cursor {
+            rest: trimmed.trim_end(),
+            line: i + 1,
+            column: text[..text.len() - trimmed.len()].chars().count() + 1,
+        };
+        items.push(line(&mut cursor)?);
+        cursor.end()?;
+    }
+    Ok(items)
+}
+
+/// Parses all of `source` with `f`, which must read all of it but for whitespace at either end.
+pub fn whole<'a, T>(
+    source: &'a str,
+    f: impl FnOnce(&mut Cursor<'a>) -> Result<T, ParseError>,
+) -> Result<T, ParseError> {
+    let mut cursor = Cursor::new(source.trim_end());
+    cursor.take_while(char::is_whitespace);
+    let value = f(&mut cursor)?;
+    cursor.end()?;
+    Ok(value)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the error is on, counting from 1.
+    pub line: usize,
+    /// The column the error is at, counting characters from 1.
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[cfg(test)]
+#[test]
+fn test_parse() {
+    fn pair<'a>(c: &mut Cursor<'a>) -> Result<(&'a str, i32), ParseError> {
+        let name = c.ident()?;
+        c.literal("=")?;
+        Ok((name, c.int()?))
+    }
+    fn error(source: &str) -> String {
+        lines(source, pair).unwrap_err().to_string()
+    }
+
+    assert_eq!(
+        lines("a=1, b=-2\n\n  c=+3\n", |c| c.sep_by(", ", pair)),
+        Ok(vec![vec![("a", 1), ("b", -2)], vec![("c", 3)]])
+    );
+    assert_eq!(
+        whole("\n 12-34 \n", |c| {
+            let start = c.int::<u32>()?;
+            c.literal("-")?;
+            Ok(start..=c.int()?)
+        }),
+        Ok(12..=34)
+    );
+
+    assert_eq!(error("a=1\n  b=x"), "2:5: expected a number, found 'x'");
+    assert_eq!(
+        error("a=1;"),
+        "1:4: expected the end of the line, found ';'"
+    );
+    assert_eq!(error("=1"), "1:1: expected a name, found '='");
+    assert_eq!(error("a"), "1:2: expected \"=\", found the end of the line");
+    assert_eq!(error("a=3000000000"), "1:3: 3000000000 is out of range");
+
+    let mut c = Cursor::new("R8 x");
+    let dir = |c: char| Some(c).filter(|c| "UDLR".contains(*c));
+    assert_eq!(c.char("a direction", dir), Ok('R'));
+    assert_eq!(c.int(), Ok(8));
+    c.spaces();
+    assert_eq!(c.rest(), "x");
+    assert_eq!(
+        c.char("a direction", dir).unwrap_err().to_string(),
+        "1:4: expected a direction, found 'x'"
+    );
+}
@@ -0,0 +1,184 @@
+//! Searching graphs given as a function from each node to its neighbours, so that a node can be a
+//! grid coordinate, a name, or a whole machine state.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// Searches breadth-first from `start` for the nearest node that `goal` accepts, returning the
+/// path to it with `start` and the goal both included.
+pub fn bfs<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::new();
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::new();
+    seen.insert(start.clone());
+    queue.push_back(start);
+    while let Some(node) = queue.pop_front() {
+        if goal(&node) {
+            return Some(path(&parents, node));
+        }
+        for next in neighbours(&node) {
+            if seen.insert(next.clone()) {
+                parents.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Visits every node reachable from `start`, returning them in layers by their distance from it:
+/// the first layer is just `start`, the next its neighbours, and so on.
+pub fn flood<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> Vec<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::new();
+    seen.insert(start.clone());
+    let mut layers = vec![vec![start]];
+    loop {
+        let mut next = Vec::new();
+        for node in &layers[layers.len() - 1] {
+            for adj in neighbours(node) {
+                if seen.insert(adj.clone()) {
+                    next.push(adj);
+                }
+            }
+        }
+        if next.is_empty() {
+            break layers;
+        }
+        layers.push(next);
+    }
+}
+
+/// Finds the cheapest path from `start` to a node that `goal` accepts, where `neighbours` gives
+/// each neighbour with the cost of the step to it. Returns the path, ends included, and its cost.
+pub fn dijkstra<N, I>(
+    start: N,
+    neighbours: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, u64)>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    astar(start, neighbours, |_| 0, goal)
+}
+
+/// Like [`dijkstra`], but trying first the nodes that `heuristic` guesses are closest to the goal.
+/// The path found is only the cheapest if the heuristic never guesses more than the real cost.
+pub fn astar<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> u64,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, u64)>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut costs = HashMap::new();
+    let mut parents = HashMap::new();
+    // the heap holds indices into `nodes`, so that nodes needn't be ordered themselves
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((heuristic(&start), 0, 0)));
+    costs.insert(start.clone(), 0);
+    let mut nodes = vec![start];
+    while let Some(Reverse((_, cost, i))) = heap.pop() {
+        let node = nodes[i].clone();
+        if costs[&node] < cost {
+            // a cheaper way here was found after this one was queued
+            continue;
+        }
+        if goal(&node) {
+            return Some((path(&parents, node), cost));
+        }
+        for (next, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            if costs.get(&next).is_none_or(|cost| next_cost < *cost) {
+                costs.insert(next.clone(), next_cost);
+                parents.insert(next.clone(), node.clone());
+                heap.push(Reverse((
+                    next_cost + heuristic(&next),
+                    next_cost,
+                    nodes.len(),
+                )));
+                nodes.push(next);
+            }
+        }
+    }
+    None
+}
+
+/// Follows `parents`, which maps each node to the one it was reached from, back from `end` to the
+/// node with no parent, returning the path between them in order.
+pub fn path<N: Clone + Eq + Hash>(parents: &HashMap<N, N>, end: N) -> Vec<N> {
+    let mut path = vec![end];
+    while let Some(parent) = parents.get(&path[path.len() - 1]) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+#[test]
+fn test_search() {
+    use crate::grid::{Coord, Grid};
+
+    let maze = Grid::parse(
+        "#######\n\
+         #S..#.#\n\
+         #.#.#.#\n\
+         #.#...#\n\
+         #...#E#\n\
+         #######",
+        Some,
+    )
+    .unwrap();
+    let open = |coord: &Coord| {
+        maze.neighbours4(*coord)
+            .filter(|adj| maze[*adj] != '#')
+            .collect::<Vec<_>>()
+    };
+    let route = bfs((1, 1), open, |coord| maze[*coord] == 'E').unwrap();
+    assert_eq!(route.len(), 8);
+    assert_eq!((route[0], route[7]), ((1, 1), (5, 4)));
+    assert!(route
+        .windows(2)
+        .all(|step| open(&step[0]).contains(&step[1])));
+    assert_eq!(bfs((1, 1), open, |coord| maze[*coord] == 'X'), None);
+
+    let layers = flood((1, 1), open);
+    assert_eq!(layers.len(), 9);
+    assert_eq!(layers[1].len(), 2);
+    assert_eq!(layers.iter().map(Vec::len).sum::<usize>(), 15);
+
+    // stepping onto a dot costs 1, but onto anything else costs 5
+    let weighted = |coord: &Coord| {
+        open(coord)
+            .into_iter()
+            .map(|adj| (adj, if maze[adj] == '.' { 1 } else { 5 }))
+            .collect::<Vec<_>>()
+    };
+    let (cheapest, cost) = dijkstra((1, 1), weighted, |coord| maze[*coord] == 'E').unwrap();
+    assert_eq!((cheapest.len(), cost), (8, 11));
+    let distance = |coord: &Coord| ((5 - coord.0).abs() + (4 - coord.1).abs()) as u64;
+    assert_eq!(
+        astar((1, 1), weighted, distance, |coord| maze[*coord] == 'E'),
+        Some((cheapest, cost))
+    );
+
+    let parents = [(3, 2), (2, 1)].iter().copied().collect::<HashMap<_, _>>();
+    assert_eq!(path(&parents, 3), [1, 2, 3]);
+}
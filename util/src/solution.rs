@@ -0,0 +1,203 @@
+use crate::animate::{self, Animation};
+use crate::bench::{self, DayBench, Stats};
+use crate::image::Image;
+use std::error;
+use std::fmt::{self, Display};
+use std::io;
+use std::time::{Duration, Instant};
+
+/// A day's puzzle: how to parse its input, and how to answer each part from the parsed input.
+pub trait Solution {
+    /// The day of the puzzle, from 1 to 25.
+    const DAY: u32;
+
+    type Input;
+    type Part1: Display;
+    type Part2: Display;
+
+    fn parse(source: &str) -> Result<Self::Input, Box<dyn error::Error>>;
+    fn part1(input: &Self::Input) -> Self::Part1;
+    fn part2(input: &Self::Input) -> Self::Part2;
+
+    /// Draws the solution at work, for the days that are worth watching. The runners call this
+    /// once a day is solved, never while timing it, and only if there's somewhere for the frames
+    /// to go.
+    fn animate(input: &Self::Input, animation: &mut Animation) -> io::Result<()> {
+        let _ = (input, animation);
+        Ok(())
+    }
+
+    /// The picture a day's answer is, for the days whose answers are pictures, to be saved by the
+    /// runners when asked to.
+    fn picture(input: &Self::Input) -> Option<Image> {
+        let _ = input;
+        None
+    }
+}
+
+/// A [`Solution`] with its types hidden, so that every day can be kept in one list.
+pub trait Day: Sync {
+    fn day(&self) -> u32;
+
+    /// Parses `source` and answers both parts, timing each step.
+    fn solve(&self, source: &str) -> Result<Report, Box<dyn error::Error>>;
+
+    /// Parses `source` and answers both parts over and over, timing each step.
+    fn bench(
+        &self,
+        source: &str,
+        options: &bench::Options,
+    ) -> Result<DayBench, Box<dyn error::Error>>;
+
+    /// Parses `source` and animates the solution, as `options` say.
+    fn animate(
+        &self,
+        source: &str,
+        options: &animate::Options,
+    ) -> Result<(), Box<dyn error::Error>>;
+
+    /// Parses `source` and draws the answer's picture, if it has one.
+    fn picture(&self, source: &str) -> Result<Option<Image>, Box<dyn error::Error>>;
+}
+
+impl<S: Solution + Sync> Day for S {
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn solve(&self, source: &str) -> Result<Report, Box<dyn error::Error>> {
+        let start = Instant::now();
+        let input = S::parse(source)?;
+        let parse = start.elapsed();
+        Ok(Report {
+            day: S::DAY,
+            parse,
+            part1: Answer::time(|| S::part1(&input)),
+            part2: Answer::time(|| S::part2(&input)),
+        })
+    }
+
+    fn bench(
+        &self,
+        source: &str,
+        options: &bench::Options,
+    ) -> Result<DayBench, Box<dyn error::Error>> {
+        let input = S::parse(source)?;
+        Ok(DayBench {
+            day: S::DAY,
+            parse: Stats::measure(options, || S::parse(source).is_ok()),
+            part1: Stats::measure(options, || S::part1(&input)),
+            part2: Stats::measure(options, || S::part2(&input)),
+        })
+    }
+
+    fn animate(
+        &self,
+        source: &str,
+        options: &animate::Options,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let input = S::parse(source)?;
+        let mut animation = Animation::new(options.for_day(S::DAY));
+        S::animate(&input, &mut animation)?;
+        animation.finish()?;
+        Ok(())
+    }
+
+    fn picture(&self, source: &str) -> Result<Option<Image>, Box<dyn error::Error>> {
+        Ok(S::picture(&S::parse(source)?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    pub value: String,
+    pub time: Duration,
+}
+
+impl Answer {
+    fn time<T: Display>(part: impl FnOnce() -> T) -> Answer {
+        let start = Instant::now();
+        let value = part();
+        let time = start.elapsed();
+        Answer {
+            value: value.to_string(),
+            time,
+        }
+    }
+}
+
+/// The answers to a day's puzzle, and how long it took to get them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub day: u32,
+    pub parse: Duration,
+    pub part1: Answer,
+    pub part2: Answer,
+}
+
+impl Report {
+    /// The time taken altogether.
+    pub fn time(&self) -> Duration {
+        self.parse + self.part1.time + self.part2.time
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "day {} (parsed in {:.1?})", self.day, self.parse)?;
+        for (part, answer) in [(1, &self.part1), (2, &self.part2)] {
+            let value = answer.value.trim_end();
+            // pictures go on the lines after the heading
+            if value.contains('\n') {
+                writeln!(f, "part {} ({:.1?}):\n{}", part, answer.time, value)?;
+            } else {
+                writeln!(f, "part {}: {} ({:.1?})", part, value, answer.time)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_solution() {
+    struct Sum;
+
+    impl Solution for Sum {
+        const DAY: u32 = 1;
+
+        type Input = Vec<i64>;
+        type Part1 = i64;
+        type Part2 = String;
+
+        fn parse(source: &str) -> Result<Vec<i64>, Box<dyn error::Error>> {
+            Ok(source
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()?)
+        }
+
+        fn part1(input: &Vec<i64>) -> i64 {
+            input.iter().sum()
+        }
+
+        fn part2(input: &Vec<i64>) -> String {
+            input.iter().map(|n| format!("{}\n", n)).collect()
+        }
+    }
+
+    let mut report = Sum.solve("1 2 3").unwrap();
+    assert_eq!((report.day, report.part1.value.as_str()), (1, "6"));
+    report.parse = Duration::from_micros(12);
+    report.part1.time = Duration::from_nanos(1500);
+    report.part2.time = Duration::from_millis(3);
+    assert_eq!(report.time(), Duration::from_nanos(3_013_500));
+    assert_eq!(
+        report.to_string(),
+        "day 1 (parsed in 12.0µs)\npart 1: 6 (1.5µs)\npart 2 (3.0ms):\n1\n2\n3\n"
+    );
+    assert_eq!(
+        Sum.solve("1 x").unwrap_err().to_string(),
+        "invalid digit found in string"
+    );
+}